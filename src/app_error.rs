@@ -0,0 +1,51 @@
+use native_kvg::error::Error;
+use std::{fmt, io};
+
+/// The errors of the library plus the ones only the command line and the
+/// settings file run into.
+#[derive(Debug)]
+pub enum AppError {
+    Kvg(Error),
+    /// a command line that does not make sense, the usage follows it
+    Usage(String),
+    #[cfg(feature = "gui")]
+    NoHomeDir,
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Kvg(err) => write!(f, "{err}"),
+            AppError::Usage(message) => write!(f, "{message}"),
+            #[cfg(feature = "gui")]
+            AppError::NoHomeDir => write!(f, "could not find the home directory"),
+        }
+    }
+}
+
+impl std::error::Error for AppError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AppError::Kvg(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<Error> for AppError {
+    fn from(err: Error) -> Self {
+        AppError::Kvg(err)
+    }
+}
+
+impl From<io::Error> for AppError {
+    fn from(err: io::Error) -> Self {
+        AppError::Kvg(err.into())
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(err: serde_json::Error) -> Self {
+        AppError::Kvg(err.into())
+    }
+}
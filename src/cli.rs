@@ -0,0 +1,302 @@
+use crate::app_error::AppError;
+use native_kvg::{
+    job::Job,
+    web_render::web_cards,
+    workbook::{column_name, find_column},
+};
+use std::{fs, path::PathBuf, process::ExitCode};
+
+pub const USAGE: &str = "\
+usage: native_kvg render --input <file> --title <text> --columns <list> --output <file>
+                         [--sheet <name>] [--header-row <number>] [--header-rows <count>]
+                         [--query <sql>] [--fill-merged] [--print-links]
+                         [--date-format <pattern>]
+                         [--column-date-format <column=pattern>]...
+                         [--column-number-format <column=code>]...
+                         [--column-formula <column=cached|text|evaluate>]...
+                         [--image-columns <list>]
+       native_kvg render --job <file> --output <file> [any option above to change the job]
+
+  --job         job file saved from the window
+  --input       workbook, csv, json or sqlite file to read
+  --sheet       sheet or table name, the first one when left out
+  --query       SELECT to run instead of a table (sqlite only)
+  --header-row  row number of the headers as excel shows it, 1 by default
+  --header-rows how many rows the headers take, grouped labels above the
+                column labels end up as keys like \"Address / City\"
+  --columns     comma separated header names or column letters, like name,C,age
+  --fill-merged every card under a merged cell gets its value
+  --print-links linked values get their address written after them
+  --date-format how dates are written, %d/%m/%Y by default
+  --column-date-format
+                the same for the one column named before the =, can be repeated
+  --column-number-format
+                excel number format like #,##0.00 or 0% for the one column
+                named before the =, instead of the format of its cells
+  --column-formula
+                what the formula cells of the column named before the = show:
+                the result saved in the workbook (the default), the formula
+                itself, or the formula worked out when nothing was saved
+  --image-columns
+                comma separated header names of columns holding picture paths
+                like photos/1234.jpg, taken from the folder of the input
+  --title       title printed on top of every card
+  --output      html file to write
+
+run without arguments to open the window";
+
+/// Everything a headless render needs, as given on the command line.
+#[derive(Debug, Default, PartialEq)]
+pub struct RenderArgs {
+    pub job: Job,
+    pub output: PathBuf,
+    /// `--columns` as typed, header names or else column letters, they
+    /// replace the columns of the job
+    pub columns: Vec<String>,
+}
+
+/// runs the command line and reports how it went through the exit code
+pub fn run(args: Vec<String>) -> ExitCode {
+    if matches!(args.first().map(String::as_str), Some("-h" | "--help")) {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    let args = match parse(args) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("Error : {err}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match render(&args) {
+        Ok(output) => {
+            println!("rendered at : {}", output.display());
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("Error : {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// options given next to `--job` win over what the job file says
+pub fn parse(args: Vec<String>) -> Result<RenderArgs, AppError> {
+    let mut args = args.into_iter();
+    match args.next().as_deref() {
+        Some("render") => (),
+        Some(other) => return Err(AppError::Usage(format!("unknown command {other}"))),
+        None => return Err(AppError::Usage("missing command".to_string())),
+    }
+
+    let mut given = Job::default();
+    let (mut job_file, mut output, mut columns) = (None, None, Vec::new());
+    while let Some(flag) = args.next() {
+        // switches take no value
+        match flag.as_str() {
+            "--fill-merged" => {
+                given.fill_merged = true;
+                continue;
+            }
+            "--print-links" => {
+                given.print_links = true;
+                continue;
+            }
+            _ => (),
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| AppError::Usage(format!("{flag} needs a value")))?;
+        match flag.as_str() {
+            "--job" => job_file = Some(PathBuf::from(value)),
+            "--input" => given.input = PathBuf::from(value),
+            "--sheet" => given.sheet = Some(value),
+            "--query" => given.query = Some(value),
+            "--header-row" => {
+                given.header_row = value
+                    .parse()
+                    .ok()
+                    .filter(|x| *x > 0)
+                    .ok_or_else(|| AppError::Usage(format!("bad header row {value}")))?;
+            }
+            "--header-rows" => {
+                given.header_rows = value
+                    .parse()
+                    .ok()
+                    .filter(|x| *x > 0)
+                    .ok_or_else(|| AppError::Usage(format!("bad header rows {value}")))?;
+            }
+            "--columns" => {
+                columns = value
+                    .split(',')
+                    .map(|x| x.trim().to_string())
+                    .filter(|x| !x.is_empty())
+                    .collect();
+            }
+            "--date-format" => given.date_format = Some(value),
+            "--column-date-format" => {
+                let (column, pattern) = value.split_once('=').ok_or_else(|| {
+                    AppError::Usage(format!("{flag} takes column=pattern, got {value}"))
+                })?;
+                given
+                    .column_date_formats
+                    .insert(column.trim().to_string(), pattern.to_string());
+            }
+            "--column-number-format" => {
+                let (column, code) = value.split_once('=').ok_or_else(|| {
+                    AppError::Usage(format!("{flag} takes column=code, got {value}"))
+                })?;
+                given
+                    .column_number_formats
+                    .insert(column.trim().to_string(), code.to_string());
+            }
+            "--column-formula" => {
+                let (column, mode) = value.split_once('=').ok_or_else(|| {
+                    AppError::Usage(format!("{flag} takes column=mode, got {value}"))
+                })?;
+                given
+                    .column_formula_modes
+                    .insert(column.trim().to_string(), mode.trim().parse()?);
+            }
+            "--image-columns" => {
+                given.image_columns = value
+                    .split(',')
+                    .map(|x| x.trim().to_string())
+                    .filter(|x| !x.is_empty())
+                    .collect();
+            }
+            "--title" => given.title = value,
+            "--output" => output = Some(PathBuf::from(value)),
+            _ => return Err(AppError::Usage(format!("unknown option {flag}"))),
+        }
+    }
+
+    let mut job = match job_file {
+        Some(path) => Job::load(&path)?,
+        None => Job::default(),
+    };
+    if given.input != PathBuf::new() {
+        job.input = given.input;
+    }
+    if given.query.is_some() || given.sheet.is_some() {
+        job.sheet = given.sheet;
+        job.query = given.query;
+    }
+    if given.header_row > 0 {
+        job.header_row = given.header_row;
+    }
+    if given.header_rows > 0 {
+        job.header_rows = given.header_rows;
+    }
+    job.fill_merged |= given.fill_merged;
+    job.print_links |= given.print_links;
+    if given.date_format.is_some() {
+        job.date_format = given.date_format;
+    }
+    job.column_date_formats.extend(given.column_date_formats);
+    job.column_number_formats
+        .extend(given.column_number_formats);
+    job.column_formula_modes.extend(given.column_formula_modes);
+    job.image_columns.extend(given.image_columns);
+    if !given.title.is_empty() {
+        job.title = given.title;
+    }
+    job.header_row = job.header_row.max(1);
+    job.header_rows = job.header_rows.max(1);
+
+    let missing = |name: &str| AppError::Usage(format!("{name} is required"));
+    if job.input == PathBuf::new() {
+        return Err(missing("--input"));
+    }
+    if job.title.is_empty() {
+        return Err(missing("--title"));
+    }
+    if job.columns.is_empty() && job.column_letters.is_empty() && columns.is_empty() {
+        return Err(missing("--columns"));
+    }
+    let output = output.ok_or_else(|| missing("--output"))?;
+    Ok(RenderArgs {
+        job,
+        output,
+        columns,
+    })
+}
+
+fn render(args: &RenderArgs) -> Result<PathBuf, AppError> {
+    let RenderArgs {
+        job,
+        output,
+        columns,
+    } = args;
+    let request = job.request();
+    let workbook = request.load()?;
+    let sheet = request.sheet(&workbook)?;
+    let titles = sheet.header_titles(request.header)?;
+    let columns = if columns.is_empty() {
+        request.column_indexes(&titles)
+    } else {
+        columns.iter().map(|x| find_column(&titles, x)).collect()
+    };
+    let columns = columns.map_err(|err| {
+        let known = titles
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| !x.is_empty())
+            .map(|(index, x)| format!("{} {x}", column_name(index)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        AppError::Usage(format!(
+            "{err}\nthe columns of row {} are : {known}",
+            request.header.row
+        ))
+    })?;
+    let html = web_cards(&request.options, request.header, sheet, columns, |_, _| {
+        true
+    })?;
+    fs::write(output, html)?;
+    Ok(output.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split(' ').map(String::from).collect()
+    }
+
+    #[test]
+    fn parses_a_full_render() {
+        let parsed = parse(args(
+            "render --input a.xlsx --sheet s --header-row 3 --columns name,C --title t --output o.html",
+        ))
+        .unwrap();
+        assert_eq!(
+            parsed,
+            RenderArgs {
+                job: Job {
+                    title: "t".into(),
+                    input: "a.xlsx".into(),
+                    sheet: Some("s".into()),
+                    query: None,
+                    header_row: 3,
+                    header_rows: 1,
+                    fill_merged: false,
+                    ..Default::default()
+                },
+                output: "o.html".into(),
+                columns: vec!["name".into(), "C".into()],
+            }
+        );
+    }
+
+    #[test]
+    fn missing_options_are_usage_errors() {
+        let err = parse(args("render --input a.xlsx --title t --output o.html")).unwrap_err();
+        assert!(matches!(err, AppError::Usage(_)));
+        let err = parse(args("render --input")).unwrap_err();
+        assert!(matches!(err, AppError::Usage(_)));
+        let err = parse(args("paint")).unwrap_err();
+        assert!(matches!(err, AppError::Usage(_)));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::error::Error;
+use calamine::{Cell, Data, Range};
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1256};
+use std::{fs, path::Path};
+
+pub const CSV_FILTERS: [&str; 2] = ["csv", "tsv"];
+
+const DELIMITERS: [u8; 4] = [b',', b'\t', b';', b'|'];
+
+/// Reads a delimited text file as if it was the only sheet of a workbook.
+pub fn load(path: &Path) -> Result<Range<Data>, Error> {
+    let bytes = fs::read(path)?;
+    let text = decode(&bytes);
+    let is_tsv = path
+        .extension()
+        .and_then(|x| x.to_str())
+        .is_some_and(|x| x.eq_ignore_ascii_case("tsv"));
+    let delimiter = if is_tsv {
+        b'\t'
+    } else {
+        detect_delimiter(&text)
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_reader(text.as_bytes());
+
+    let mut cells = Vec::new();
+    for (row, record) in reader.records().enumerate() {
+        let record = record?;
+        for (col, field) in record.iter().enumerate() {
+            if !field.is_empty() {
+                cells.push(Cell::new(
+                    (row as u32, col as u32),
+                    Data::String(field.to_string()),
+                ));
+            }
+        }
+    }
+    Ok(Range::from_sparse(cells))
+}
+
+/// BOM wins, then utf-8 if the bytes are valid, and windows-1256 otherwise
+/// since that is what arabic excel exports as
+fn decode(bytes: &[u8]) -> String {
+    let encoding = match Encoding::for_bom(bytes) {
+        Some((encoding, _)) => encoding,
+        None if std::str::from_utf8(bytes).is_ok() => UTF_8,
+        None => WINDOWS_1256,
+    };
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
+
+/// picks the candidate that shows up the same number of times on most of the
+/// leading lines, ties go to the more frequent one
+fn detect_delimiter(text: &str) -> u8 {
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|x| !x.trim().is_empty())
+        .take(20)
+        .collect();
+    DELIMITERS
+        .into_iter()
+        .map(|delimiter| {
+            let counts: Vec<usize> = lines
+                .iter()
+                .map(|line| count_outside_quotes(line, delimiter))
+                .collect();
+            let first = counts.first().copied().unwrap_or_default();
+            let consistent = counts.iter().filter(|x| **x == first).count();
+            let total: usize = counts.iter().sum();
+            (delimiter, first > 0, consistent, total)
+        })
+        .filter(|(_, found, _, _)| *found)
+        .max_by_key(|(_, _, consistent, total)| (*consistent, *total))
+        .map(|(delimiter, ..)| delimiter)
+        .unwrap_or(b',')
+}
+
+fn count_outside_quotes(line: &str, delimiter: u8) -> usize {
+    let mut quoted = false;
+    line.bytes()
+        .filter(|x| {
+            if *x == b'"' {
+                quoted = !quoted;
+            }
+            !quoted && *x == delimiter
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delimiters_are_detected() {
+        assert_eq!(detect_delimiter("name;age\nahmed;30\n"), b';');
+        assert_eq!(detect_delimiter("name\tage\nahmed\t30\n"), b'\t');
+        assert_eq!(detect_delimiter("name,age\nahmed,30\n"), b',');
+        // commas inside quotes do not count
+        assert_eq!(
+            detect_delimiter("name;city\n\"eltahawy, m\";\"cairo, eg\"\n"),
+            b';'
+        );
+        assert_eq!(detect_delimiter("just one column\n"), b',');
+    }
+
+    #[test]
+    fn text_is_decoded_before_splitting() {
+        let bom = b"\xEF\xBB\xBFname;age\nahmed;30\n";
+        let text = decode(bom);
+        assert_eq!(text, "name;age\nahmed;30\n");
+        assert_eq!(detect_delimiter(&text), b';');
+
+        let (arabic, _, _) = WINDOWS_1256.encode("الاسم;العمر\nمحمد;30\n");
+        assert!(std::str::from_utf8(&arabic).is_err());
+        let text = decode(&arabic);
+        assert_eq!(text, "الاسم;العمر\nمحمد;30\n");
+        assert_eq!(detect_delimiter(&text), b';');
+    }
+}
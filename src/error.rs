@@ -0,0 +1,96 @@
+use std::{fmt, io};
+
+/// Anything that can go wrong between picking a file and writing the cards.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Calamine(calamine::Error),
+    Csv(csv::Error),
+    Json(serde_json::Error),
+    Sqlite(rusqlite::Error),
+    Zip(zip::result::ZipError),
+    Xml(quick_xml::Error),
+    SheetNotFound(String),
+    RowOutOfRange(usize),
+    ColumnNotFound(String),
+    UnknownFormulaMode(String),
+    Cancelled,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{err}"),
+            Error::Calamine(err) => write!(f, "{err}"),
+            Error::Csv(err) => write!(f, "{err}"),
+            Error::Json(err) => write!(f, "{err}"),
+            Error::Sqlite(err) => write!(f, "{err}"),
+            Error::Zip(err) => write!(f, "{err}"),
+            Error::Xml(err) => write!(f, "{err}"),
+            Error::SheetNotFound(name) => write!(f, "there is no sheet named {name}"),
+            Error::RowOutOfRange(number) => write!(f, "row {number} is outside the sheet"),
+            Error::ColumnNotFound(name) => {
+                write!(f, "no column is named {name} and it is not a column letter")
+            }
+            Error::UnknownFormulaMode(name) => write!(f, "unknown formula mode {name}"),
+            Error::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Calamine(err) => Some(err),
+            Error::Csv(err) => Some(err),
+            Error::Json(err) => Some(err),
+            Error::Sqlite(err) => Some(err),
+            Error::Zip(err) => Some(err),
+            Error::Xml(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<calamine::Error> for Error {
+    fn from(err: calamine::Error) -> Self {
+        Error::Calamine(err)
+    }
+}
+
+impl From<csv::Error> for Error {
+    fn from(err: csv::Error) -> Self {
+        Error::Csv(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        Error::Sqlite(err)
+    }
+}
+
+impl From<zip::result::ZipError> for Error {
+    fn from(err: zip::result::ZipError) -> Self {
+        Error::Zip(err)
+    }
+}
+
+impl From<quick_xml::Error> for Error {
+    fn from(err: quick_xml::Error) -> Self {
+        Error::Xml(err)
+    }
+}
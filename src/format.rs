@@ -0,0 +1,414 @@
+use calamine::{Data, DataType};
+use chrono::{
+    Duration, NaiveDateTime,
+    format::{Item, Numeric, StrftimeItems},
+};
+use std::fmt::Write;
+
+/// day/month/year, the way dates are written around here
+pub const DEFAULT_DATE_FORMAT: &str = "%d/%m/%Y";
+
+/// true when chrono understands every `%` of `pattern`
+pub fn is_valid_date_format(pattern: &str) -> bool {
+    !pattern.is_empty() && StrftimeItems::new(pattern).all(|x| !matches!(x, Item::Error))
+}
+
+/// the text of a cell as it goes on a card, dates use `date_format` and
+/// durations are written as hours:minutes:seconds. A time of day uses
+/// `date_format` too when it shows the time, hours:minutes otherwise
+pub fn cell_text(value: &Data, date_format: &str) -> String {
+    let text = match value {
+        Data::DateTime(x) if x.is_duration() => x.as_duration().map(duration_text),
+        // a serial below one is a time of day without a date
+        Data::DateTime(x) if x.as_f64() < 1. => {
+            let pattern = if shows_time(date_format) {
+                date_format
+            } else {
+                "%H:%M"
+            };
+            x.as_datetime().map(|x| date_text(x, pattern))
+        }
+        Data::DateTime(x) => x.as_datetime().map(|x| date_text(x, date_format)),
+        Data::DateTimeIso(_) => value
+            .as_datetime()
+            .or_else(|| value.as_date().and_then(|x| x.and_hms_opt(0, 0, 0)))
+            .map(|x| date_text(x, date_format)),
+        Data::DurationIso(_) => value.as_duration().map(duration_text),
+        Data::Float(x) => Some(general_text(*x)),
+        _ => None,
+    };
+    text.unwrap_or_else(|| value.to_string())
+}
+
+/// true when `pattern` is valid and has an hour, minute or second in it
+fn shows_time(pattern: &str) -> bool {
+    is_valid_date_format(pattern)
+        && StrftimeItems::new(pattern).any(|x| {
+            matches!(
+                x,
+                Item::Numeric(
+                    Numeric::Hour | Numeric::Hour12 | Numeric::Minute | Numeric::Second,
+                    _
+                )
+            )
+        })
+}
+
+/// a number written the way the excel number format `code` says: decimals,
+/// thousands separators, percent, currency symbols and the like. `None` for
+/// cells that are not numbers and for date or text formats
+pub fn number_text(value: &Data, code: &str) -> Option<String> {
+    let value = match value {
+        Data::Int(x) => *x as f64,
+        Data::Float(x) => *x,
+        _ => return None,
+    };
+    let sections = sections(code);
+    // a negative section writes its own sign, like (1,234)
+    let (section, value) = match sections.as_slice() {
+        [] => return None,
+        [_, negative, ..] if value < 0. => (negative, -value),
+        [_, _, zero, ..] if value == 0. => (zero, value),
+        [positive, ..] => (positive, value),
+    };
+    section_text(section, value)
+}
+
+/// excel shows about ten significant digits, which hides the float noise
+/// of values like 1234.5000000001
+pub(crate) fn general_text(value: f64) -> String {
+    if value == 0. || !value.is_finite() || value.abs() >= 1e15 || value.abs() < 1e-9 {
+        return value.to_string();
+    }
+    let digits = value.abs().log10().floor() as i32 + 1;
+    let decimals = (10 - digits).max(0) as usize;
+    let text = format!("{value:.decimals$}");
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    }
+}
+
+/// the `;` separated sections of a format code, quoted text kept whole
+fn sections(code: &str) -> Vec<String> {
+    let mut sections = vec![String::new()];
+    let mut chars = code.chars();
+    while let Some(x) = chars.next() {
+        let current = sections.last_mut().unwrap();
+        match x {
+            ';' => sections.push(String::new()),
+            '"' => {
+                current.push(x);
+                for x in chars.by_ref() {
+                    current.push(x);
+                    if x == '"' {
+                        break;
+                    }
+                }
+            }
+            '\\' => {
+                current.push(x);
+                current.extend(chars.next());
+            }
+            x => current.push(x),
+        }
+    }
+    sections
+}
+
+enum Token {
+    Text(String),
+    Digit(char),
+    Dot,
+    Comma,
+    Exponent(char),
+}
+
+fn section_text(section: &str, value: f64) -> Option<String> {
+    if section.trim().eq_ignore_ascii_case("general") {
+        return Some(general_text(value));
+    }
+    let mut tokens = Vec::new();
+    let mut percent = 0;
+    let mut chars = section.chars().peekable();
+    while let Some(x) = chars.next() {
+        let token = match x {
+            '"' => Token::Text(chars.by_ref().take_while(|x| *x != '"').collect()),
+            '\\' => Token::Text(chars.next()?.to_string()),
+            '_' => {
+                chars.next();
+                Token::Text(" ".to_string())
+            }
+            '*' => {
+                chars.next();
+                continue;
+            }
+            '[' => {
+                let inner: String = chars.by_ref().take_while(|x| *x != ']').collect();
+                match inner.strip_prefix('$') {
+                    // [$€-407] is a currency symbol with a locale after the dash
+                    Some(currency) => {
+                        Token::Text(currency.split('-').next().unwrap_or_default().to_string())
+                    }
+                    // [h] and friends are elapsed times, colors and conditions
+                    // are dropped
+                    None if inner.chars().all(|x| "hHmMsS".contains(x)) => return None,
+                    None => continue,
+                }
+            }
+            '0' | '#' | '?' => Token::Digit(x),
+            '.' => Token::Dot,
+            ',' => Token::Comma,
+            '%' => {
+                percent += 1;
+                Token::Text("%".to_string())
+            }
+            'E' | 'e' if matches!(chars.peek(), Some('+' | '-')) => Token::Exponent(chars.next()?),
+            // text and date formats are left to the other writers
+            '@' => return None,
+            x if "yYmMdDhHsS".contains(x) => return None,
+            x => Token::Text(x.to_string()),
+        };
+        tokens.push(token);
+    }
+
+    let text = |tokens: &[Token]| {
+        tokens
+            .iter()
+            .filter_map(|x| match x {
+                Token::Text(x) => Some(x.as_str()),
+                _ => None,
+            })
+            .collect::<String>()
+    };
+    // a section without digits, like "zero" in 0.00;-0.00;"zero", is
+    // written as it is
+    let Some(first) = tokens.iter().position(|x| matches!(x, Token::Digit(_))) else {
+        return Some(text(&tokens));
+    };
+    let last = tokens.iter().rposition(|x| matches!(x, Token::Digit(_)))?;
+    // commas right after the digits divide by a thousand each
+    let scale = tokens[last + 1..]
+        .iter()
+        .take_while(|x| matches!(x, Token::Comma))
+        .count();
+    let number = &tokens[first..=last];
+    let exponent_at = number.iter().position(|x| matches!(x, Token::Exponent(_)));
+    let mantissa = &number[..exponent_at.unwrap_or(number.len())];
+    let dot_at = mantissa.iter().position(|x| matches!(x, Token::Dot));
+    let (integer, fraction) = mantissa.split_at(dot_at.unwrap_or(mantissa.len()));
+    let count = |tokens: &[Token], zeros_only: bool| {
+        tokens
+            .iter()
+            .filter(|x| match x {
+                Token::Digit('0') => true,
+                Token::Digit(_) => !zeros_only,
+                _ => false,
+            })
+            .count()
+    };
+    let pattern = Pattern {
+        integer_min: count(integer, true),
+        fraction_min: count(fraction, true),
+        fraction_max: count(fraction, false),
+        grouping: integer.iter().any(|x| matches!(x, Token::Comma)),
+        dot: dot_at.is_some(),
+    };
+    if mantissa
+        .iter()
+        .any(|x| matches!(x, Token::Text(_) | Token::Exponent(_)))
+    {
+        return None;
+    }
+
+    let scaled = value * 100f64.powi(percent) / 1000f64.powi(scale as i32);
+    let number_text = match exponent_at {
+        Some(at) => {
+            let Token::Exponent(sign) = number[at] else {
+                return None;
+            };
+            let exponent_digits = count(&number[at + 1..], true).max(1);
+            let mut exponent = if scaled == 0. {
+                0
+            } else {
+                scaled.abs().log10().floor() as i32
+            };
+            let mut mantissa = scaled.abs() / 10f64.powi(exponent);
+            // rounding can turn 9.99 into 10.0
+            if round(mantissa, pattern.fraction_max) >= 10. {
+                mantissa /= 10.;
+                exponent += 1;
+            }
+            let sign = match (exponent < 0, sign) {
+                (true, _) => "-",
+                (false, '+') => "+",
+                _ => "",
+            };
+            format!(
+                "{}E{sign}{:0exponent_digits$}",
+                pattern.write(mantissa),
+                exponent.abs()
+            )
+        }
+        None => pattern.write(scaled.abs()),
+    };
+
+    let shows_nonzero = number_text.chars().any(|x| x.is_ascii_digit() && x != '0');
+    let sign = if scaled < 0. && shows_nonzero {
+        "-"
+    } else {
+        ""
+    };
+    Some(format!(
+        "{sign}{}{number_text}{}",
+        text(&tokens[..first]),
+        text(&tokens[last + 1..])
+    ))
+}
+
+/// the digit placeholders of a format, `0` always shows and `#` or `?`
+/// only when needed
+struct Pattern {
+    integer_min: usize,
+    fraction_min: usize,
+    fraction_max: usize,
+    grouping: bool,
+    dot: bool,
+}
+
+impl Pattern {
+    fn write(&self, value: f64) -> String {
+        let value = round(value, self.fraction_max);
+        let text = format!("{value:.0$}", self.fraction_max);
+        let (integer, fraction) = text.split_once('.').unwrap_or((&text, ""));
+        let mut fraction = fraction.to_string();
+        while fraction.len() > self.fraction_min && fraction.ends_with('0') {
+            fraction.pop();
+        }
+        let integer = integer.trim_start_matches('0');
+        let integer = format!("{integer:0>0$}", self.integer_min);
+        let integer = if self.grouping {
+            let digits: Vec<char> = integer.chars().collect();
+            digits
+                .rchunks(3)
+                .rev()
+                .map(|x| x.iter().collect::<String>())
+                .collect::<Vec<_>>()
+                .join(",")
+        } else {
+            integer
+        };
+        if self.dot {
+            format!("{integer}.{fraction}")
+        } else {
+            integer
+        }
+    }
+}
+
+/// `value` rounded to `decimals` places the way excel does it, halves away
+/// from zero where `format!` would round them to even. Excel only keeps 15
+/// significant digits, so 1.005 (stored as 1.00499999…) is a half to it
+fn round(value: f64, decimals: usize) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    let scaled = value * factor;
+    let scaled = format!("{scaled:.14e}").parse().unwrap_or(scaled);
+    scaled.round() / factor
+}
+
+fn date_text(value: NaiveDateTime, date_format: &str) -> String {
+    let date_format = if is_valid_date_format(date_format) {
+        date_format
+    } else {
+        DEFAULT_DATE_FORMAT
+    };
+    let mut text = String::new();
+    match write!(text, "{}", value.format(date_format)) {
+        Ok(()) => text,
+        Err(_) => value.format(DEFAULT_DATE_FORMAT).to_string(),
+    }
+}
+
+fn duration_text(value: Duration) -> String {
+    let seconds = value.num_seconds();
+    let sign = if seconds < 0 { "-" } else { "" };
+    let seconds = seconds.abs();
+    format!(
+        "{sign}{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calamine::{ExcelDateTime, ExcelDateTimeType};
+
+    fn excel(value: f64, kind: ExcelDateTimeType) -> Data {
+        Data::DateTime(ExcelDateTime::new(value, kind, false))
+    }
+
+    #[test]
+    fn dates_times_and_durations_are_readable() {
+        let date = excel(45306.5, ExcelDateTimeType::DateTime);
+        assert_eq!(cell_text(&date, DEFAULT_DATE_FORMAT), "15/01/2024");
+        assert_eq!(cell_text(&date, "%Y-%m-%d %H:%M"), "2024-01-15 12:00");
+        let time = excel(0.75, ExcelDateTimeType::DateTime);
+        assert_eq!(cell_text(&time, DEFAULT_DATE_FORMAT), "18:00");
+        assert_eq!(cell_text(&time, "%I:%M %p"), "06:00 PM");
+        assert_eq!(cell_text(&time, "%T"), "18:00:00");
+        let duration = excel(1.5, ExcelDateTimeType::TimeDelta);
+        assert_eq!(cell_text(&duration, DEFAULT_DATE_FORMAT), "36:00:00");
+        let iso = Data::DateTimeIso("2024-01-15".into());
+        assert_eq!(cell_text(&iso, DEFAULT_DATE_FORMAT), "15/01/2024");
+        assert_eq!(cell_text(&Data::Int(7), DEFAULT_DATE_FORMAT), "7");
+    }
+
+    #[test]
+    fn numbers_follow_their_excel_format() {
+        let number = |x: f64, code: &str| number_text(&Data::Float(x), code);
+        assert_eq!(number(0.15, "0%").as_deref(), Some("15%"));
+        assert_eq!(number(0.1234, "0.00%").as_deref(), Some("12.34%"));
+        assert_eq!(number(1234.5000000001, "0.00").as_deref(), Some("1234.50"));
+        assert_eq!(
+            number(1234567.891, "#,##0.00").as_deref(),
+            Some("1,234,567.89")
+        );
+        assert_eq!(number(-1234., "#,##0;(#,##0)").as_deref(), Some("(1,234)"));
+        assert_eq!(number(-5., "0.0").as_deref(), Some("-5.0"));
+        assert_eq!(
+            number(12.5, "[$€-407] #,##0.00").as_deref(),
+            Some("€ 12.50")
+        );
+        assert_eq!(
+            number(99., "#,##0.00 \"ج.م\"").as_deref(),
+            Some("99.00 ج.م")
+        );
+        assert_eq!(number(1500000., "#,##0,\"K\"").as_deref(), Some("1,500K"));
+        assert_eq!(number(12345., "0.00E+00").as_deref(), Some("1.23E+04"));
+        assert_eq!(
+            number(1234.5000000001, "General").as_deref(),
+            Some("1234.5")
+        );
+        assert_eq!(number(2.5, "0").as_deref(), Some("3"));
+        assert_eq!(number(1234.5, "#,##0").as_deref(), Some("1,235"));
+        assert_eq!(number(0.125, "0.00").as_deref(), Some("0.13"));
+        assert_eq!(number(1.005, "0.00").as_deref(), Some("1.01"));
+        assert_eq!(number(2.675, "0.00").as_deref(), Some("2.68"));
+        assert_eq!(number(-0.5, "0").as_deref(), Some("-1"));
+        assert_eq!(number(0., "0.00;-0.00;\"zero\"").as_deref(), Some("zero"));
+        assert_eq!(number(45306., "dd/mm/yyyy"), None);
+        assert_eq!(number_text(&Data::String("x".into()), "0.00"), None);
+    }
+
+    #[test]
+    fn bad_patterns_fall_back_to_the_default() {
+        assert!(!is_valid_date_format("%Q"));
+        assert!(is_valid_date_format("%d-%m"));
+        let date = excel(45306., ExcelDateTimeType::DateTime);
+        assert_eq!(cell_text(&date, "%Q"), "15/01/2024");
+    }
+}
@@ -0,0 +1,463 @@
+use crate::{
+    error::Error,
+    format::general_text,
+    workbook::{Sheet, column_index},
+};
+use calamine::Data;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    str::FromStr,
+};
+
+/// What a formula cell shows on a card.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FormulaMode {
+    /// the result the workbook saved with the formula
+    #[default]
+    Cached,
+    /// the formula itself, like `=A2*B2`
+    Text,
+    /// the saved result, or the formula worked out here when there is none
+    Evaluate,
+}
+
+impl FormulaMode {
+    pub const ALL: [Self; 3] = [Self::Cached, Self::Text, Self::Evaluate];
+}
+
+impl fmt::Display for FormulaMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Cached => "cached",
+            Self::Text => "text",
+            Self::Evaluate => "evaluate",
+        })
+    }
+}
+
+impl FromStr for FormulaMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|x| x.to_string() == s)
+            .ok_or_else(|| Error::UnknownFormulaMode(s.to_string()))
+    }
+}
+
+/// what a cell holding `formula` shows under `mode`, `cached` being the
+/// value the workbook saved along with it
+pub fn formula_value(sheet: &Sheet, formula: &str, cached: &Data, mode: FormulaMode) -> Data {
+    let text = || Data::String(format!("={formula}"));
+    match mode {
+        FormulaMode::Cached => cached.clone(),
+        FormulaMode::Text => text(),
+        FormulaMode::Evaluate if *cached != Data::Empty => cached.clone(),
+        FormulaMode::Evaluate => evaluate(sheet, formula).unwrap_or_else(text),
+    }
+}
+
+/// works out `formula` (without its `=`) on `sheet`. Only numbers, quoted
+/// text, references to cells of the same sheet, `+ - * / ^ %` and `&` are
+/// understood, `None` for anything else like functions or ranges
+pub fn evaluate(sheet: &Sheet, formula: &str) -> Option<Data> {
+    let tokens = tokens(formula)?;
+    let mut evaluator = Evaluator {
+        sheet,
+        cells: HashMap::new(),
+    };
+    evaluator.prepare(&tokens);
+    let value = evaluator.run(&tokens)?;
+    Some(match value {
+        Value::Number(x) => Data::Float(x),
+        Value::Text(x) => Data::String(x),
+        Value::Empty => Data::Float(0.),
+    })
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    /// (row, column) counted from 0
+    Cell(u32, u32),
+    Operator(char),
+    Open,
+    Close,
+}
+
+fn tokens(formula: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = formula.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' => {
+                chars.next();
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            '+' | '-' | '*' | '/' | '^' | '&' | '%' => {
+                chars.next();
+                tokens.push(Token::Operator(c));
+            }
+            '"' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next()? {
+                        // a doubled quote is a quote inside the text
+                        '"' if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            text.push('"');
+                        }
+                        '"' => break,
+                        c => text.push(c),
+                    }
+                }
+                tokens.push(Token::Text(text));
+            }
+            '0'..='9' | '.' => {
+                let mut number = String::new();
+                while let Some(&c) = chars.peek() {
+                    let exponent_sign = matches!(c, '+' | '-') && number.ends_with(['e', 'E']);
+                    if !(c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E') || exponent_sign) {
+                        break;
+                    }
+                    number.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Number(number.parse().ok()?));
+            }
+            'A'..='Z' | 'a'..='z' | '$' => {
+                let mut name = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '$') {
+                        break;
+                    }
+                    name.push(c);
+                    chars.next();
+                }
+                // a name that is not a cell is a function, a named range or
+                // another sheet, none of which are worked out here
+                tokens.push(cell(&name)?);
+            }
+            _ => return None,
+        }
+    }
+    Some(tokens)
+}
+
+/// `$B$3` as the cell at row 2, column 1
+fn cell(name: &str) -> Option<Token> {
+    let name = name.replace('$', "");
+    let split = name.find(|x: char| x.is_ascii_digit())?;
+    let (letters, digits) = name.split_at(split);
+    let row = digits.parse::<u32>().ok()?.checked_sub(1)?;
+    Some(Token::Cell(row, column_index(letters)? as u32))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Number(f64),
+    Text(String),
+    /// a blank cell, zero in sums and nothing in text
+    Empty,
+}
+
+impl Value {
+    fn number(&self) -> Option<f64> {
+        match self {
+            Value::Number(x) => Some(*x),
+            Value::Text(x) => x.trim().parse().ok(),
+            Value::Empty => Some(0.),
+        }
+    }
+
+    fn text(&self) -> String {
+        match self {
+            Value::Number(x) => general_text(*x),
+            Value::Text(x) => x.clone(),
+            Value::Empty => String::new(),
+        }
+    }
+}
+
+/// the cells a formula refers to
+fn references(tokens: &[Token]) -> impl Iterator<Item = (u32, u32)> + '_ {
+    tokens.iter().filter_map(|x| match x {
+        Token::Cell(row, col) => Some((*row, *col)),
+        _ => None,
+    })
+}
+
+struct Evaluator<'a> {
+    sheet: &'a Sheet,
+    /// the formula cells without a saved result worked out so far, `None`
+    /// for the ones that can not be, like the cells of a loop
+    cells: HashMap<(u32, u32), Option<Value>>,
+}
+
+impl<'a> Evaluator<'a> {
+    /// the formula of a cell that has no saved result
+    fn unsaved_formula(&self, position: (u32, u32)) -> Option<&'a str> {
+        let sheet: &'a Sheet = self.sheet;
+        if sheet
+            .range
+            .get_value(position)
+            .is_some_and(|x| *x != Data::Empty)
+        {
+            return None;
+        }
+        sheet
+            .formulas()
+            .get_value(position)
+            .map(String::as_str)
+            .filter(|x| !x.is_empty())
+    }
+
+    /// works out every unsaved formula cell `formula` depends on, the deepest
+    /// first, so a long chain of references is a loop here rather than a
+    /// recursion that could run out of stack, and every cell is worked out
+    /// once however many cells refer to it
+    fn prepare(&mut self, formula: &[Token]) {
+        // a cell is pushed once to look at what it refers to, then again
+        // to be worked out after all of that
+        let mut stack: Vec<((u32, u32), bool)> = references(formula).map(|x| (x, false)).collect();
+        let mut in_progress = HashSet::new();
+        while let Some((position, referred)) = stack.pop() {
+            if self.cells.contains_key(&position) {
+                continue;
+            }
+            let Some(formula) = self.unsaved_formula(position) else {
+                continue;
+            };
+            let tokens = tokens(formula);
+            if referred {
+                in_progress.remove(&position);
+                let value = tokens.and_then(|x| self.run(&x));
+                self.cells.insert(position, value);
+            } else if !in_progress.insert(position) {
+                // met again while working out what it refers to, a loop
+                self.cells.insert(position, None);
+            } else {
+                stack.push((position, true));
+                let refers_to = tokens.iter().flat_map(|x| references(x));
+                stack.extend(refers_to.map(|x| (x, false)));
+            }
+        }
+    }
+
+    /// the value of `tokens`, the cells they refer to being prepared
+    fn run(&self, tokens: &[Token]) -> Option<Value> {
+        let mut parser = Parser {
+            evaluator: self,
+            tokens,
+            at: 0,
+        };
+        let value = parser.concatenation()?;
+        (parser.at == tokens.len()).then_some(value)
+    }
+
+    fn cell(&self, position: (u32, u32)) -> Option<Value> {
+        if self.unsaved_formula(position).is_some() {
+            return self.cells.get(&position).cloned().flatten();
+        }
+        let value = self
+            .sheet
+            .range
+            .get_value(position)
+            .cloned()
+            .unwrap_or(Data::Empty);
+        let value = match value {
+            Data::Empty => Value::Empty,
+            Data::Int(x) => Value::Number(x as f64),
+            Data::Float(x) => Value::Number(x),
+            Data::Bool(x) => Value::Number(if x { 1. } else { 0. }),
+            Data::DateTime(x) => Value::Number(x.as_f64()),
+            Data::String(x) => Value::Text(x),
+            Data::DateTimeIso(_) | Data::DurationIso(_) | Data::Error(_) => return None,
+        };
+        Some(value)
+    }
+}
+
+/// Recursive descent over the tokens, one method per level of excel's
+/// operator precedence from the loosest to the tightest.
+struct Parser<'a> {
+    evaluator: &'a Evaluator<'a>,
+    tokens: &'a [Token],
+    at: usize,
+}
+
+impl Parser<'_> {
+    fn operator(&mut self, operators: &[char]) -> Option<char> {
+        match self.tokens.get(self.at) {
+            Some(Token::Operator(x)) if operators.contains(x) => {
+                self.at += 1;
+                Some(*x)
+            }
+            _ => None,
+        }
+    }
+
+    fn concatenation(&mut self) -> Option<Value> {
+        let mut value = self.sum()?;
+        while self.operator(&['&']).is_some() {
+            let right = self.sum()?;
+            value = Value::Text(value.text() + &right.text());
+        }
+        Some(value)
+    }
+
+    fn sum(&mut self) -> Option<Value> {
+        let mut value = self.product()?;
+        while let Some(operator) = self.operator(&['+', '-']) {
+            let (left, right) = (value.number()?, self.product()?.number()?);
+            value = Value::Number(match operator {
+                '+' => left + right,
+                _ => left - right,
+            });
+        }
+        Some(value)
+    }
+
+    fn product(&mut self) -> Option<Value> {
+        let mut value = self.power()?;
+        while let Some(operator) = self.operator(&['*', '/']) {
+            let (left, right) = (value.number()?, self.power()?.number()?);
+            value = Value::Number(match operator {
+                '*' => left * right,
+                _ if right == 0. => return None,
+                _ => left / right,
+            });
+        }
+        Some(value)
+    }
+
+    fn power(&mut self) -> Option<Value> {
+        let mut value = self.percent()?;
+        while self.operator(&['^']).is_some() {
+            let right = self.percent()?.number()?;
+            value = Value::Number(value.number()?.powf(right));
+        }
+        Some(value)
+    }
+
+    fn percent(&mut self) -> Option<Value> {
+        let mut value = self.negation()?;
+        while self.operator(&['%']).is_some() {
+            value = Value::Number(value.number()? / 100.);
+        }
+        Some(value)
+    }
+
+    /// excel binds a leading minus tighter than `^`, so -2^2 is 4
+    fn negation(&mut self) -> Option<Value> {
+        match self.operator(&['-', '+']) {
+            Some('-') => Some(Value::Number(-self.negation()?.number()?)),
+            Some(_) => self.negation(),
+            None => self.operand(),
+        }
+    }
+
+    fn operand(&mut self) -> Option<Value> {
+        let token = self.tokens.get(self.at)?.clone();
+        self.at += 1;
+        match token {
+            Token::Number(x) => Some(Value::Number(x)),
+            Token::Text(x) => Some(Value::Text(x)),
+            Token::Cell(row, col) => self.evaluator.cell((row, col)),
+            Token::Open => {
+                let value = self.concatenation()?;
+                (self.tokens.get(self.at) == Some(&Token::Close)).then(|| {
+                    self.at += 1;
+                    value
+                })
+            }
+            Token::Operator(_) | Token::Close => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use calamine::{Cell, Range};
+
+    #[test]
+    fn simple_formulas_are_worked_out() {
+        let mut sheet = Sheet::new(
+            "sheet".into(),
+            Range::from_sparse(vec![
+                Cell::new((0, 0), Data::Float(3.)),
+                Cell::new((0, 1), Data::String("kg".into())),
+            ]),
+        );
+        // C1 has no saved result, D1 refers to itself
+        sheet.formulas = Range::from_sparse(vec![
+            Cell::new((0, 2), "A1*2".to_string()),
+            Cell::new((0, 3), "D1+1".to_string()),
+            // F1 and G1 refer to each other
+            Cell::new((0, 5), "G1*2".to_string()),
+            Cell::new((0, 6), "F1+1".to_string()),
+        ])
+        .into();
+        let evaluate = |formula| evaluate(&sheet, formula);
+        assert_eq!(evaluate("1+2*3"), Some(Data::Float(7.)));
+        assert_eq!(evaluate("(1+2)*3"), Some(Data::Float(9.)));
+        assert_eq!(evaluate("-2^2"), Some(Data::Float(4.)));
+        assert_eq!(evaluate("50%*$A$1"), Some(Data::Float(1.5)));
+        assert_eq!(evaluate("C1&\" \"&B1"), Some(Data::String("6 kg".into())));
+        assert_eq!(
+            evaluate("\"say \"\"hi\"\"\""),
+            Some(Data::String("say \"hi\"".into()))
+        );
+        assert_eq!(evaluate("0.1+0.2&\"\""), Some(Data::String("0.3".into())));
+        assert_eq!(evaluate("E1+1"), Some(Data::Float(1.)));
+        assert_eq!(evaluate("SUM(A1:A3)"), None);
+        assert_eq!(evaluate("A1/0"), None);
+        assert_eq!(evaluate("B1*2"), None);
+        assert_eq!(evaluate("D1"), None);
+        assert_eq!(evaluate("F1"), None);
+        assert_eq!(
+            formula_value(&sheet, "A1*2", &Data::Empty, FormulaMode::Text),
+            Data::String("=A1*2".into())
+        );
+        assert_eq!(
+            formula_value(&sheet, "A1*2", &Data::Int(5), FormulaMode::Evaluate),
+            Data::Int(5)
+        );
+        assert_eq!(
+            "evaluate".parse::<FormulaMode>().unwrap(),
+            FormulaMode::Evaluate
+        );
+    }
+
+    #[test]
+    fn long_chains_are_worked_out_once() {
+        let mut sheet = Sheet::new(
+            "sheet".into(),
+            Range::from_sparse(vec![Cell::new((0, 0), Data::Float(1.))]),
+        );
+        let mut formulas = Vec::new();
+        for row in 1..5000u32 {
+            // A doubles every row, B is a running total of A
+            formulas.push(Cell::new((row, 0), format!("A{row}+A{row}")));
+            formulas.push(Cell::new((row, 1), format!("B{row}+A{}", row + 1)));
+        }
+        formulas.push(Cell::new((0, 1), "A1".to_string()));
+        sheet.formulas = Range::from_sparse(formulas).into();
+        assert_eq!(evaluate(&sheet, "A60"), Some(Data::Float(2f64.powi(59))));
+        assert_eq!(
+            evaluate(&sheet, "B40"),
+            Some(Data::Float(2f64.powi(40) - 1.))
+        );
+        assert!(matches!(evaluate(&sheet, "B5000"), Some(Data::Float(_))));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{
+    error::Error,
+    format::DEFAULT_DATE_FORMAT,
+    formula::FormulaMode,
+    request::{Column, RenderOptions, RenderRequest},
+    workbook::Header,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// job files get their own extension so they are never taken for json data
+pub const JOB_FILTERS: [&str; 1] = ["kvgjob"];
+
+/// A saved render setup, so the same deck can be rebuilt without clicking
+/// through everything again. Columns are kept by header name so the job
+/// still works after the columns of the sheet are reordered, only columns
+/// whose name another column shares are kept by their letters.
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Job {
+    pub title: String,
+    pub input: PathBuf,
+    pub sheet: Option<String>,
+    pub query: Option<String>,
+    pub header_row: usize,
+    /// rows the header spans, one when left out
+    pub header_rows: usize,
+    /// header names, never taken for column letters
+    pub columns: Vec<String>,
+    /// excel letters of the columns that share their header name with
+    /// another column, they go on the cards after the named ones
+    pub column_letters: Vec<String>,
+    pub fill_merged: bool,
+    pub print_links: bool,
+    /// chrono pattern for dates, day/month/year when left out
+    pub date_format: Option<String>,
+    /// date patterns of single columns by header name
+    pub column_date_formats: BTreeMap<String, String>,
+    /// excel number formats of single columns by header name
+    pub column_number_formats: BTreeMap<String, String>,
+    /// what formula cells of single columns show by header name
+    pub column_formula_modes: BTreeMap<String, FormulaMode>,
+    /// header names of the columns that hold picture file paths
+    pub image_columns: BTreeSet<String>,
+}
+
+impl Job {
+    /// a relative `input` is taken from the folder of the job file
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let mut job: Job = serde_json::from_str(&fs::read_to_string(path)?)?;
+        if job.input.is_relative()
+            && let Some(dir) = path.parent()
+        {
+            job.input = dir.join(&job.input);
+        }
+        Ok(job)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// the render this job describes
+    pub fn request(&self) -> RenderRequest {
+        RenderRequest {
+            source: self.input.clone(),
+            sheet: self.sheet.clone(),
+            query: self.query.clone(),
+            header: Header {
+                row: self.header_row.max(1),
+                rows: self.header_rows.max(1),
+            },
+            columns: (self.columns.iter().cloned().map(Column::Named))
+                .chain(self.column_letters.iter().cloned().map(Column::Letters))
+                .collect(),
+            options: RenderOptions {
+                title: self.title.clone(),
+                fill_merged: self.fill_merged,
+                print_links: self.print_links,
+                date_format: self
+                    .date_format
+                    .clone()
+                    .unwrap_or_else(|| DEFAULT_DATE_FORMAT.to_string()),
+                column_date_formats: self.column_date_formats.clone(),
+                column_number_formats: self.column_number_formats.clone(),
+                column_formula_modes: self.column_formula_modes.clone(),
+                image_columns: self.image_columns.clone(),
+                image_dir: self.input.parent().map(PathBuf::from).unwrap_or_default(),
+            },
+        }
+    }
+
+    /// the column indexes of the job columns among `titles`, a name that
+    /// is no longer there is an error instead of a silently missing column
+    pub fn column_indexes(&self, titles: &[String]) -> Result<Vec<usize>, Error> {
+        self.request().column_indexes(titles)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_follow_their_headers() {
+        let job = Job {
+            columns: vec!["age".into(), "name".into()],
+            ..Default::default()
+        };
+        let before = ["name".to_string(), "age".to_string()];
+        let after = ["id".to_string(), "age".to_string(), "name".to_string()];
+        assert_eq!(job.column_indexes(&before).unwrap(), vec![1, 0]);
+        assert_eq!(job.column_indexes(&after).unwrap(), vec![1, 2]);
+        let renamed = ["full name".to_string(), "age".to_string()];
+        assert!(matches!(
+            job.column_indexes(&renamed),
+            Err(Error::ColumnNotFound(name)) if name == "name"
+        ));
+        // a short header is not read as column letters once it is gone
+        let renamed = ["name".to_string(), "years".to_string()];
+        assert!(matches!(
+            job.column_indexes(&renamed),
+            Err(Error::ColumnNotFound(name)) if name == "age"
+        ));
+        let job = Job {
+            columns: vec!["x".into()],
+            column_letters: vec!["C".into()],
+            ..Default::default()
+        };
+        let twice = ["x".to_string(), "y".to_string(), "x".to_string()];
+        assert_eq!(job.column_indexes(&twice).unwrap(), vec![0, 2]);
+    }
+}
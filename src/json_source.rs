@@ -0,0 +1,160 @@
+use crate::error::Error;
+use calamine::{Cell, Data, Range};
+use serde_json::{Map, Value};
+use std::{fs, path::Path};
+
+pub const JSON_FILTERS: [&str; 3] = ["json", "jsonl", "ndjson"];
+
+/// Reads a json array (or json lines) of records as a single sheet, the keys
+/// of the records become the first row and nested objects turn into dotted keys
+pub fn load(path: &Path) -> Result<Range<Data>, Error> {
+    let text = fs::read_to_string(path)?;
+    let text = text.trim_start_matches('\u{feff}');
+    let is_lines = path
+        .extension()
+        .and_then(|x| x.to_str())
+        .is_some_and(|x| !x.eq_ignore_ascii_case("json"));
+    let records = if is_lines {
+        parse_lines(text)?
+    } else {
+        match serde_json::from_str(text) {
+            Ok(Value::Array(values)) => values,
+            Ok(value) => vec![value],
+            // plenty of exporters write json lines into a .json file
+            Err(err) => parse_lines(text).map_err(|_| err)?,
+        }
+    };
+
+    let mut keys: Vec<String> = Vec::new();
+    let mut rows = Vec::new();
+    for record in records {
+        let mut flat = Vec::new();
+        match record {
+            Value::Object(map) => flatten("", map, &mut flat),
+            value => flat.push(("value".to_string(), value)),
+        }
+        let row: Vec<(usize, Value)> = flat
+            .into_iter()
+            .map(|(key, value)| {
+                let col = match keys.iter().position(|x| *x == key) {
+                    Some(col) => col,
+                    None => {
+                        keys.push(key);
+                        keys.len() - 1
+                    }
+                };
+                (col, value)
+            })
+            .collect();
+        rows.push(row);
+    }
+
+    let mut cells: Vec<Cell<Data>> = keys
+        .into_iter()
+        .enumerate()
+        .map(|(col, key)| Cell::new((0, col as u32), Data::String(key)))
+        .collect();
+    for (row, values) in rows.into_iter().enumerate() {
+        for (col, value) in values {
+            let value = to_data(value);
+            if value != Data::Empty {
+                cells.push(Cell::new((row as u32 + 1, col as u32), value));
+            }
+        }
+    }
+    Ok(Range::from_sparse(cells))
+}
+
+fn parse_lines(text: &str) -> Result<Vec<Value>, serde_json::Error> {
+    text.lines()
+        .filter(|x| !x.trim().is_empty())
+        .map(serde_json::from_str)
+        .collect()
+}
+
+fn flatten(prefix: &str, map: Map<String, Value>, out: &mut Vec<(String, Value)>) {
+    for (key, value) in map {
+        let key = if prefix.is_empty() {
+            key
+        } else {
+            format!("{prefix}.{key}")
+        };
+        match value {
+            Value::Object(map) => flatten(&key, map, out),
+            value => out.push((key, value)),
+        }
+    }
+}
+
+fn to_data(value: Value) -> Data {
+    match value {
+        Value::Null => Data::Empty,
+        Value::Bool(x) => Data::Bool(x),
+        Value::Number(x) => match x.as_i64() {
+            Some(x) => Data::Int(x),
+            None => Data::Float(x.as_f64().unwrap_or_default()),
+        },
+        Value::String(x) => Data::String(x),
+        Value::Array(values) if values.iter().all(|x| !x.is_array() && !x.is_object()) => {
+            let values: Vec<String> = values
+                .into_iter()
+                .filter(|x| !x.is_null())
+                .map(|x| match x {
+                    Value::String(x) => x,
+                    x => x.to_string(),
+                })
+                .collect();
+            Data::String(values.join(", "))
+        }
+        value => Data::String(value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_path::TempPath;
+
+    #[test]
+    fn nested_records_are_flattened() {
+        let path = TempPath::new("nested.json");
+        fs::write(
+            &path,
+            r#"[{"name": "ahmed", "address": {"city": "cairo", "geo": {"lat": 30.04}}, "tags": ["a", 1, null]},
+               {"name": "mona", "phones": [{"home": "123"}], "age": null}]"#,
+        )
+        .unwrap();
+        let range = load(&path).unwrap();
+        let row = |row: u32| -> Vec<Data> {
+            (0..range.width() as u32)
+                .map(|col| range.get_value((row, col)).cloned().unwrap_or_default())
+                .collect()
+        };
+        let text = |x: &str| Data::String(x.into());
+        assert_eq!(
+            row(0),
+            [
+                text("name"),
+                text("address.city"),
+                text("address.geo.lat"),
+                text("tags"),
+                text("phones"),
+                text("age"),
+            ]
+        );
+        assert_eq!(
+            row(1),
+            [
+                text("ahmed"),
+                text("cairo"),
+                Data::Float(30.04),
+                text("a, 1"),
+                Data::Empty,
+                Data::Empty,
+            ]
+        );
+        assert_eq!(row(2)[4], text(r#"[{"home":"123"}]"#));
+        let missing = TempPath::new("missing.json");
+        assert!(matches!(load(&missing), Err(Error::Io(_))));
+    }
+}
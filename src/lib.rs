@@ -0,0 +1,23 @@
+//! Turns the rows of a spreadsheet, csv, json or sqlite file into printable
+//! html cards, one card per row with the picked columns as key and value.
+//!
+//! [`RenderRequest`] covers the whole trip from a file to cards or html,
+//! [`web_render::web_cards`] renders a sheet that is already loaded.
+
+pub mod csv_source;
+pub mod error;
+pub mod format;
+pub mod formula;
+pub mod job;
+pub mod json_source;
+pub mod request;
+pub mod sqlite_source;
+#[cfg(test)]
+mod temp_path;
+pub mod web_render;
+pub mod workbook;
+pub mod xlsx_parts;
+
+pub use error::Error;
+pub use request::{Column, RenderOptions, RenderRequest};
+pub use web_render::{Card, Kv};
@@ -0,0 +1,192 @@
+use crate::{
+    error::Error,
+    format::DEFAULT_DATE_FORMAT,
+    formula::FormulaMode,
+    sqlite_source::QUERY_SHEET,
+    web_render::{Card, get_cards, web_cards},
+    workbook::{Header, Sheet, Workbook, column_index, column_name},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+
+/// A column to put on the cards: its index counted from column A, its
+/// header name or its excel letters like `C`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Column {
+    Index(usize),
+    /// only ever matched against the header titles, a short header like
+    /// `age` is not taken for the column letters AGE
+    Named(String),
+    Letters(String),
+}
+
+impl Column {
+    /// the index of this column among the header `titles`, a column that is
+    /// not there is `Error::ColumnNotFound`
+    pub fn index(&self, titles: &[String]) -> Result<usize, Error> {
+        let (index, name) = match self {
+            Column::Index(index) => (Some(*index), column_name(*index)),
+            Column::Named(name) => (
+                titles.iter().position(|x| x.trim() == name.trim()),
+                name.trim().to_string(),
+            ),
+            Column::Letters(letters) => (column_index(letters.trim()), letters.trim().to_string()),
+        };
+        index
+            .filter(|x| *x < titles.len())
+            .ok_or(Error::ColumnNotFound(name))
+    }
+}
+
+/// How the cards look, the same for every card of a render.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderOptions {
+    pub title: String,
+    /// every card under a merged data cell gets its value, not just the
+    /// first one
+    pub fill_merged: bool,
+    /// linked values get their address printed after them, for paper where
+    /// a link cannot be clicked
+    pub print_links: bool,
+    /// chrono pattern for date cells
+    pub date_format: String,
+    /// date patterns of single columns by header name, they win over
+    /// `date_format`
+    pub column_date_formats: BTreeMap<String, String>,
+    /// excel number formats of single columns by header name, they win
+    /// over the format of the cell in the workbook
+    pub column_number_formats: BTreeMap<String, String>,
+    /// what formula cells of single columns show by header name, the
+    /// cached result for columns left out
+    pub column_formula_modes: BTreeMap<String, FormulaMode>,
+    /// header names of the columns that hold picture file paths, the
+    /// pictures go on the cards instead of the paths
+    pub image_columns: BTreeSet<String>,
+    /// where relative picture paths start, the folder of the workbook
+    pub image_dir: PathBuf,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            fill_merged: false,
+            print_links: false,
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            column_date_formats: BTreeMap::new(),
+            column_number_formats: BTreeMap::new(),
+            column_formula_modes: BTreeMap::new(),
+            image_columns: BTreeSet::new(),
+            image_dir: PathBuf::new(),
+        }
+    }
+}
+
+impl RenderOptions {
+    /// the date pattern of the column titled `header`
+    pub fn date_format(&self, header: &str) -> &str {
+        self.column_date_formats
+            .get(header)
+            .unwrap_or(&self.date_format)
+    }
+
+    /// the number format picked for the column titled `header`, if any
+    pub fn number_format(&self, header: &str) -> Option<&str> {
+        self.column_number_formats.get(header).map(String::as_str)
+    }
+
+    /// what the formula cells of the column titled `header` show
+    pub fn formula_mode(&self, header: &str) -> FormulaMode {
+        self.column_formula_modes
+            .get(header)
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+/// Everything needed to turn a file into cards.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RenderRequest {
+    pub source: PathBuf,
+    /// the first sheet when `None`, ignored when there is a `query`
+    pub sheet: Option<String>,
+    /// a SELECT to run when `source` is a database
+    pub query: Option<String>,
+    /// the excel rows of the headers
+    pub header: Header,
+    pub columns: Vec<Column>,
+    pub options: RenderOptions,
+}
+
+impl RenderRequest {
+    /// reads `source`, with the query run when there is one
+    pub fn load(&self) -> Result<Workbook, Error> {
+        let workbook = Workbook::load(&self.source)?;
+        match &self.query {
+            Some(sql) => workbook.with_query(sql),
+            None => Ok(workbook),
+        }
+    }
+
+    /// the sheet of `workbook` this request is about
+    pub fn sheet<'a>(&self, workbook: &'a Workbook) -> Result<&'a Sheet, Error> {
+        let name = match (&self.query, &self.sheet) {
+            (Some(_), _) => QUERY_SHEET.to_string(),
+            (None, Some(sheet)) => sheet.clone(),
+            (None, None) => workbook
+                .sheet_names()
+                .into_iter()
+                .next()
+                .ok_or_else(|| Error::SheetNotFound(String::new()))?,
+        };
+        workbook.sheet(&name).ok_or(Error::SheetNotFound(name))
+    }
+
+    /// the indexes of `columns` among the header `titles`
+    pub fn column_indexes(&self, titles: &[String]) -> Result<Vec<usize>, Error> {
+        self.columns.iter().map(|x| x.index(titles)).collect()
+    }
+
+    pub fn cards(&self) -> Result<Vec<Card>, Error> {
+        let workbook = self.load()?;
+        let sheet = self.sheet(&workbook)?;
+        let columns = self.column_indexes(&sheet.header_titles(self.header)?)?;
+        get_cards(&self.options, self.header, sheet, columns, |_, _| true)
+    }
+
+    /// the cards as a complete html page
+    pub fn html(&self) -> Result<String, Error> {
+        let workbook = self.load()?;
+        let sheet = self.sheet(&workbook)?;
+        let columns = self.column_indexes(&sheet.header_titles(self.header)?)?;
+        web_cards(&self.options, self.header, sheet, columns, |_, _| true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_are_picked_by_index_name_or_letter() {
+        let request = RenderRequest {
+            columns: vec![
+                Column::Named("age".into()),
+                Column::Index(0),
+                Column::Letters("C".into()),
+            ],
+            ..Default::default()
+        };
+        let titles = ["name".to_string(), "age".to_string(), "id".to_string()];
+        assert_eq!(request.column_indexes(&titles).unwrap(), vec![1, 0, 2]);
+        // names are never letters, and letters past the headers are gone
+        for column in [Column::Named("B".into()), Column::Letters("AGE".into())] {
+            assert!(matches!(
+                column.index(&titles),
+                Err(Error::ColumnNotFound(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::app_error::AppError;
+use native_kvg::format::DEFAULT_DATE_FORMAT;
+use serde::{Deserialize, Serialize};
+use std::{env::home_dir, fs, path::PathBuf};
+
+pub const DEFAULT_TEMPLATE: &str = "{title}_{sheet}_{date}.html";
+
+/// What the app remembers between runs, kept as json in the home directory.
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub output_dir: Option<PathBuf>,
+    pub file_template: String,
+    pub date_format: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            output_dir: None,
+            file_template: DEFAULT_TEMPLATE.to_string(),
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+        }
+    }
+}
+
+fn settings_path() -> Option<PathBuf> {
+    home_dir().map(|x| x.join(".native_kvg.json"))
+}
+
+impl Settings {
+    /// falls back to the defaults when there is nothing saved yet
+    pub fn load() -> Self {
+        settings_path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), AppError> {
+        let path = settings_path().ok_or(AppError::NoHomeDir)?;
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// the folder the save dialog opens in
+    pub fn output_dir(&self) -> Option<PathBuf> {
+        self.output_dir.clone().or_else(home_dir)
+    }
+
+    /// fills `{title}`, `{sheet}`, `{file}`, `{date}` and `{time}` of the
+    /// template, characters that can not be in a file name become `-`
+    pub fn file_name(&self, title: &str, sheet: &str, file: &str) -> String {
+        let now = chrono::Local::now();
+        let name = self
+            .file_template
+            .replace("{title}", title)
+            .replace("{sheet}", sheet)
+            .replace("{file}", file)
+            .replace("{date}", &now.format("%Y-%m-%d").to_string())
+            .replace("{time}", &now.format("%H-%M-%S").to_string());
+        let name = sanitize_file_name(&name);
+        if name.is_empty() {
+            "kvg_index.html".to_string()
+        } else if name.to_lowercase().ends_with(".html") {
+            name
+        } else {
+            format!("{name}.html")
+        }
+    }
+}
+
+/// `name` with the characters that can not be in a file name turned into `-`
+/// and the spaces around it trimmed
+pub fn sanitize_file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|x| match x {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            x if x.is_control() => '-',
+            x => x,
+        })
+        .collect();
+    name.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_names_lose_what_a_file_system_refuses() {
+        let settings = Settings {
+            file_template: "{title}_{sheet}".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            settings.file_name("a/b:c", "x*y?", "data.xlsx"),
+            "a-b-c_x-y-.html"
+        );
+        assert_eq!(settings.file_name("\"r\"\t", "<1>|", ""), "-r--_-1--.html");
+        let settings = Settings {
+            file_template: " {title} ".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(settings.file_name("", "", ""), "kvg_index.html");
+        assert_eq!(settings.file_name("Report.HTML", "", ""), "Report.HTML");
+        assert_eq!(sanitize_file_name(" a\\b "), "a-b");
+    }
+}
@@ -0,0 +1,117 @@
+use calamine::{Cell, Data, Range};
+use rusqlite::{Connection, OpenFlags, types::ValueRef};
+use std::path::Path;
+
+pub const SQLITE_FILTERS: [&str; 3] = ["sqlite", "sqlite3", "db"];
+
+/// name of the extra sheet holding the result of a user typed query
+pub const QUERY_SHEET: &str = "استعلام";
+
+fn connect(path: &Path) -> rusqlite::Result<Connection> {
+    Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+}
+
+/// Every table and view of the database as `(name, rows)`, where the first
+/// row holds the column names. A view that cannot be read, like one over a
+/// dropped table, is left out instead of failing the whole database.
+pub fn load(path: &Path) -> rusqlite::Result<Vec<(String, Range<Data>)>> {
+    let conn = connect(path)?;
+    let names = conn
+        .prepare(
+            "SELECT name FROM sqlite_master \
+             WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' \
+             ORDER BY name",
+        )?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let sheets = names
+        .into_iter()
+        .filter_map(|name| {
+            let sql = format!("SELECT * FROM \"{}\"", name.replace('"', "\"\""));
+            let range = select(&conn, &sql).ok()?;
+            Some((name, range))
+        })
+        .collect();
+    Ok(sheets)
+}
+
+/// Runs a single read only statement against the database.
+pub fn query(path: &Path, sql: &str) -> rusqlite::Result<Range<Data>> {
+    select(&connect(path)?, sql)
+}
+
+fn select(conn: &Connection, sql: &str) -> rusqlite::Result<Range<Data>> {
+    let mut stmt = conn.prepare(sql)?;
+    let mut cells: Vec<Cell<Data>> = stmt
+        .column_names()
+        .into_iter()
+        .enumerate()
+        .map(|(col, name)| Cell::new((0, col as u32), Data::String(name.to_string())))
+        .collect();
+    let width = stmt.column_count();
+    let mut rows = stmt.query([])?;
+    let mut row_index = 1;
+    while let Some(row) = rows.next()? {
+        for col in 0..width {
+            let value = to_data(row.get_ref(col)?);
+            if value != Data::Empty {
+                cells.push(Cell::new((row_index, col as u32), value));
+            }
+        }
+        row_index += 1;
+    }
+    Ok(Range::from_sparse(cells))
+}
+
+fn to_data(value: ValueRef<'_>) -> Data {
+    match value {
+        ValueRef::Null => Data::Empty,
+        ValueRef::Integer(x) => Data::Int(x),
+        ValueRef::Real(x) => Data::Float(x),
+        ValueRef::Text(x) => Data::String(String::from_utf8_lossy(x).into_owned()),
+        ValueRef::Blob(x) => Data::String(format!("[{} bytes]", x.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{temp_path::TempPath, workbook::Workbook};
+
+    #[test]
+    fn broken_views_are_left_out() {
+        let path = TempPath::new("views.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE people (name TEXT, age INTEGER);
+             INSERT INTO people VALUES ('ahmed', 30);
+             CREATE TABLE gone (x INTEGER);
+             CREATE VIEW broken AS SELECT x FROM gone;
+             DROP TABLE gone;",
+        )
+        .unwrap();
+        drop(conn);
+        let sheets = load(&path);
+        let workbook = Workbook::load(&path).and_then(|x| {
+            let queried = x.with_query("SELECT age * 2 AS double FROM people")?;
+            Ok((x, queried))
+        });
+
+        let sheets = sheets.unwrap();
+        assert_eq!(sheets.len(), 1);
+        assert_eq!(sheets[0].0, "people");
+        assert_eq!(sheets[0].1.get_value((1, 1)), Some(&Data::Int(30)));
+        // the query sheet is added next to the same loaded tables
+        let (workbook, queried) = workbook.unwrap();
+        assert_eq!(queried.sheet_names(), ["people", QUERY_SHEET]);
+        assert!(std::ptr::eq(
+            workbook.sheet("people").unwrap(),
+            queried.sheet("people").unwrap()
+        ));
+        let query = queried.sheet(QUERY_SHEET).unwrap();
+        assert_eq!(query.range.get_value((1, 0)), Some(&Data::Int(60)));
+    }
+}
@@ -0,0 +1,145 @@
+use iced::{
+    Alignment, Element,
+    widget::{Button, Column, Text, column, row, text},
+};
+use std::fmt::Display;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Level {
+    Info,
+    Error,
+}
+
+/// One line in the status area, `context` tells where it happened
+/// (file, sheet, row) so it still makes sense when read from the history.
+#[derive(Clone)]
+pub struct Notification {
+    pub level: Level,
+    pub context: String,
+    pub message: String,
+    pub dismissed: bool,
+}
+
+#[derive(Default)]
+pub struct Status {
+    notifications: Vec<Notification>,
+    show_history: bool,
+}
+
+#[derive(Clone)]
+pub enum StatusMessage {
+    Dismiss(usize),
+    DismissAll,
+    ToggleHistory,
+    ClearHistory,
+}
+
+impl Status {
+    pub fn info(&mut self, context: String, message: impl Display) {
+        self.push(Level::Info, context, message);
+    }
+
+    pub fn error(&mut self, context: String, message: impl Display) {
+        self.push(Level::Error, context, message);
+    }
+
+    fn push(&mut self, level: Level, context: String, message: impl Display) {
+        self.notifications.push(Notification {
+            level,
+            context,
+            message: message.to_string(),
+            dismissed: false,
+        });
+    }
+
+    pub fn update(&mut self, message: StatusMessage) {
+        match message {
+            StatusMessage::Dismiss(index) => {
+                if let Some(x) = self.notifications.get_mut(index) {
+                    x.dismissed = true;
+                }
+            }
+            StatusMessage::DismissAll => {
+                self.notifications
+                    .iter_mut()
+                    .for_each(|x| x.dismissed = true);
+            }
+            StatusMessage::ToggleHistory => {
+                self.show_history = !self.show_history;
+            }
+            StatusMessage::ClearHistory => {
+                self.notifications.clear();
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, StatusMessage> {
+        let active = self
+            .notifications
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, x)| !x.dismissed)
+            .fold(Column::new(), |acc, (index, x)| {
+                let dismiss = Button::new("x").on_press(StatusMessage::Dismiss(index));
+                acc.push(
+                    row![dismiss, notification_view(x)]
+                        .spacing(10.)
+                        .align_y(Alignment::Center),
+                )
+            })
+            .spacing(5.)
+            .align_x(Alignment::End);
+
+        let history_button = (!self.notifications.is_empty()).then(|| {
+            Button::new(if self.show_history {
+                "اخفاء السجل"
+            } else {
+                "السجل"
+            })
+            .on_press(StatusMessage::ToggleHistory)
+        });
+        let dismiss_all = self
+            .notifications
+            .iter()
+            .any(|x| !x.dismissed)
+            .then(|| Button::new("اخفاء الكل").on_press(StatusMessage::DismissAll));
+        let controls = row![history_button, dismiss_all].spacing(10.);
+
+        let history = self.show_history.then(|| {
+            let list = self
+                .notifications
+                .iter()
+                .rev()
+                .fold(Column::new(), |acc, x| acc.push(notification_view(x)))
+                .spacing(5.)
+                .align_x(Alignment::End);
+            let clear = Button::new("مسح السجل").on_press(StatusMessage::ClearHistory);
+            column![clear, list].spacing(10.).align_x(Alignment::End)
+        });
+
+        column![controls, active, history]
+            .spacing(10.)
+            .align_x(Alignment::End)
+            .into()
+    }
+}
+
+fn notification_view(notification: &Notification) -> Element<'_, StatusMessage> {
+    let Notification {
+        level,
+        context,
+        message,
+        ..
+    } = notification;
+    let style = match level {
+        Level::Info => text::success,
+        Level::Error => text::danger,
+    };
+    let line = if context.is_empty() {
+        message.clone()
+    } else {
+        format!("{context} : {message}")
+    };
+    Text::new(line).style(style).into()
+}
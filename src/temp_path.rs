@@ -0,0 +1,44 @@
+//! Files the tests write, removed again even when a test panics.
+
+use std::{
+    fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+    process,
+};
+
+/// A path in the temp dir, unique to the test run, whose file or folder is
+/// removed when it is dropped.
+pub struct TempPath(PathBuf);
+
+impl TempPath {
+    /// `name` tells the tests apart, they run side by side in one process
+    pub fn new(name: &str) -> Self {
+        Self(std::env::temp_dir().join(format!("kvg-{}-{name}", process::id())))
+    }
+}
+
+impl Deref for TempPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        // nothing to clean up when the test never wrote it
+        let _ = if self.0.is_dir() {
+            fs::remove_dir_all(&self.0)
+        } else {
+            fs::remove_file(&self.0)
+        };
+    }
+}
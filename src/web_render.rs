@@ -1,24 +1,33 @@
+use crate::{
+    error::Error,
+    format::{cell_text, number_text},
+    formula::formula_value,
+    request::RenderOptions,
+    workbook::{Header, Sheet},
+    xlsx_parts::{Image, Run, TextStyle, image_mime},
+};
 use leptos::{either::Either, prelude::*};
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
 
 const CSS: &str = include_str!("../index.css");
 
+/// a dashed and crossed out frame where a picture file could not be read
+pub const MISSING_IMAGE: &str = "data:image/svg+xml,%3Csvg xmlns='http://www.w3.org/2000/svg' \
+    width='120' height='90'%3E%3Crect x='1' y='1' width='118' height='88' fill='none' \
+    stroke='%23999' stroke-width='2' stroke-dasharray='6 4'/%3E%3Cpath d='M30 25L90 65M90 25L30 65' \
+    stroke='%23999' stroke-width='2'/%3E%3C/svg%3E";
+
+/// renders a sheet that is already loaded, see `get_cards` for the rest
 pub fn web_cards(
-    title: String,
-    title_row_index: usize,
-    path: &PathBuf,
-    sheet: &str,
+    options: &RenderOptions,
+    header: Header,
+    sheet: &Sheet,
     columns_indexs: Vec<usize>,
-) -> String {
-    let cards = get_cards(title_row_index, path, sheet, columns_indexs);
-    let cards = match cards {
-        Ok(cards) => Either::Left(view! {<Cards cards title/>}),
-        Err(err) => Either::Right(view! {
-            <h3>something bad happend</h3>
-            <p>{err.to_string()}</p>
-        }),
-    };
-    view! {
+    on_row: impl FnMut(usize, usize) -> bool,
+) -> Result<String, Error> {
+    let cards = get_cards(options, header, sheet, columns_indexs, on_row)?;
+    let title = options.title.clone();
+    let html = view! {
         <!DOCTYPE html>
         <html dir="rtl" lang="ar">
             <head>
@@ -29,25 +38,57 @@ pub fn web_cards(
             </head>
             <body>
                 <p class="text-xs text-left p-3 print:hidden">made by mahmoud eltahawy</p>
-                {cards}
+                <Cards cards title print_links=options.print_links/>
             </body>
         </html>
     }
-    .to_html()
+    .to_html();
+    Ok(html)
 }
 
 #[component]
-pub fn Cards(title: String, cards: Vec<Vec<Kv>>) -> impl IntoView {
+pub fn Cards(
+    title: String,
+    cards: Vec<Card>,
+    #[prop(optional)] print_links: bool,
+) -> impl IntoView {
     let cards = cards
         .into_iter()
         .map(|kvs| {
             let kvs = kvs
                 .into_iter()
-                .map(|Kv { key, value }| {
+                .map(|Kv { key, value, link, image, style, runs }| {
+                    let text = if runs.is_empty() {
+                        Either::Left(value.clone())
+                    } else {
+                        Either::Right(
+                            runs.into_iter()
+                                .map(|Run { text, style }| {
+                                    view! { <span style=style.map(|x| x.css())>{text}</span> }
+                                })
+                                .collect_view(),
+                        )
+                    };
+                    let value = match link {
+                        Some(href) => {
+                            // the address is only worth printing when the
+                            // text does not show it already
+                            let address = href.strip_prefix("mailto:").unwrap_or(&href);
+                            let address = (print_links && value != address).then(|| {
+                                let address = format!(" ({address})");
+                                view! { <span class="text-xs">{address}</span> }
+                            });
+                            Either::Left(view! { <a class="underline" href=href>{text}</a> {address} })
+                        }
+                        None => Either::Right(text),
+                    };
+                    let image = image.map(|src| {
+                        view! { <img src=src alt=key.clone() style="display: block; max-width: 100%; max-height: 10rem; margin: auto"/> }
+                    });
                     view! {
                          <div class="flex">
                             <dt class="text-sm px-2 border-l-2 border-dotted font-bold">{key}</dt>
-                            <dd class="grow text-sm">{value}</dd>
+                            <dd class="grow text-sm" style=(!style.is_plain()).then(|| style.css())>{image} {value}</dd>
                         </div>
                     }
                 })
@@ -73,46 +114,345 @@ pub fn Cards(title: String, cards: Vec<Vec<Kv>>) -> impl IntoView {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Kv {
     pub key: String,
     pub value: String,
+    /// where the cell links to, if it is a hyperlink
+    pub link: Option<String>,
+    /// the picture in the cell as a `data:` url, empty in the cards of
+    /// `first_cards` which only tell where pictures go
+    pub image: Option<String>,
+    /// the font of the cell
+    pub style: TextStyle,
+    /// the value again in differently styled pieces, empty when it is all
+    /// in the font of the cell
+    pub runs: Vec<Run>,
 }
 
-fn get_cards(
-    title_row_index: usize,
-    path: &PathBuf,
-    sheet: &str,
+/// One card, a key and value for every picked column that has a value.
+pub type Card = Vec<Kv>;
+
+/// `header` tells the excel rows of the headers, the cards start right under
+/// them, and `columns_indexs` are column indexes counted from column A.
+/// `on_row` gets (done, total) after every row, returning false cancels the
+/// whole thing with `Error::Cancelled`. Cells missing from short rows count
+/// as empty.
+pub fn get_cards(
+    options: &RenderOptions,
+    header: Header,
+    sheet: &Sheet,
     columns_indexs: Vec<usize>,
-) -> Result<Vec<Vec<Kv>>, calamine::Error> {
-    use calamine::{Data, DeError, RangeDeserializerBuilder, Reader, Xlsx, open_workbook};
+    mut on_row: impl FnMut(usize, usize) -> bool,
+) -> Result<Vec<Card>, Error> {
+    let headers = sheet.header_titles(header)?;
 
-    let mut workbook: Xlsx<_> = open_workbook(path)?;
-    let range = workbook.worksheet_range(sheet)?;
+    let rows = header.data_row()..sheet.row_numbers().end;
+    let total = rows.clone().count();
+    let mut cards = Vec::new();
+    for number in rows {
+        cards.push(card(
+            options,
+            &headers,
+            sheet,
+            number,
+            &columns_indexs,
+            true,
+        ));
+        if !on_row(cards.len(), total) {
+            return Err(Error::Cancelled);
+        }
+    }
 
-    let mut iter = RangeDeserializerBuilder::new()
-        .has_headers(false)
-        .from_range(&range)?;
+    Ok(cards)
+}
 
-    let headers = iter
-        .nth(Into::<usize>::into(title_row_index) - 1)
-        .unwrap_or(Err(DeError::HeaderNotFound(format!(
-            "Error number {title_row_index} should contain headers"
-        ))))?;
+/// the first `count` cards only, cheap enough to redo on every change as
+/// pictures are not read, see `Kv::image`
+pub fn first_cards(
+    options: &RenderOptions,
+    header: Header,
+    sheet: &Sheet,
+    columns_indexs: &[usize],
+    count: usize,
+) -> Result<Vec<Card>, Error> {
+    let headers = sheet.header_titles(header)?;
+    let cards = (header.data_row()..sheet.row_numbers().end)
+        .take(count)
+        .map(|number| card(options, &headers, sheet, number, columns_indexs, false))
+        .collect();
+    Ok(cards)
+}
 
-    let mut cards = Vec::new();
-    for row in iter {
-        let mut kvs = Vec::new();
-        let row: Vec<Data> = row?;
-        for index in columns_indexs.iter() {
-            let header = headers[*index].to_string();
-            let value = row[*index].to_string();
-            if !header.is_empty() && !value.is_empty() {
-                kvs.push(Kv { key: header, value });
+fn card(
+    options: &RenderOptions,
+    headers: &[String],
+    sheet: &Sheet,
+    number: usize,
+    columns_indexs: &[usize],
+    image_data: bool,
+) -> Card {
+    let row = if options.fill_merged {
+        sheet.filled_row(number)
+    } else {
+        sheet.row(number)
+    };
+    let mut kvs = Vec::new();
+    for index in columns_indexs.iter() {
+        let (Some(header), Some(value)) = (headers.get(*index), row.get(*index)) else {
+            continue;
+        };
+        let cell = match sheet.formula(number, *index) {
+            Some(formula) => formula_value(sheet, formula, value, options.formula_mode(header)),
+            None => value.clone(),
+        };
+        let value = options
+            .number_format(header)
+            .or_else(|| sheet.number_format(number, *index))
+            .and_then(|code| number_text(&cell, code))
+            .unwrap_or_else(|| cell_text(&cell, options.date_format(header)));
+        let link = sheet
+            .hyperlink(number, *index)
+            .filter(|x| is_safe_link(x))
+            .map(String::from);
+        let image = match sheet.image(number, *index) {
+            Some(image) if image_data => Some(image.data_url()),
+            Some(_) => Some(String::new()),
+            None if options.image_columns.contains(header) && !value.is_empty() => {
+                Some(match image_path(options, &value) {
+                    Some(path) if image_data => path_image(&path),
+                    Some(path) if path.is_file() => String::new(),
+                    _ => MISSING_IMAGE.to_string(),
+                })
             }
+            None => None,
+        };
+        // a picture path is not worth printing once the picture is there
+        let value = if options.image_columns.contains(header) {
+            String::new()
+        } else {
+            value
+        };
+        let style = sheet
+            .text_style(number, *index)
+            .cloned()
+            .unwrap_or_default();
+        // runs only fit when the text is shown just as the workbook has it
+        let runs = sheet
+            .runs(number, *index)
+            .filter(|runs| runs.iter().map(|x| x.text.as_str()).collect::<String>() == value)
+            .map(<[Run]>::to_vec)
+            .unwrap_or_default();
+        if !header.is_empty() && (!value.is_empty() || image.is_some()) {
+            kvs.push(Kv {
+                key: header.clone(),
+                value,
+                link,
+                image,
+                style,
+                runs,
+            });
         }
-        cards.push(kvs);
     }
+    kvs
+}
+
+/// where the picture named in an image column is under
+/// `RenderOptions::image_dir`, `None` for a file that is not a picture or
+/// a path that is absolute or climbs out with `..`
+fn image_path(options: &RenderOptions, path: &str) -> Option<PathBuf> {
+    // paths written on windows work elsewhere too
+    let path = PathBuf::from(path.trim().replace('\\', "/"));
+    let inside = path
+        .components()
+        .all(|x| matches!(x, Component::Normal(_) | Component::CurDir));
+    (inside && image_mime(&path.to_string_lossy()).is_some()).then(|| options.image_dir.join(path))
+}
+
+/// the picture at `path` as a `data:` url, or the missing picture frame
+/// when it cannot be read
+fn path_image(path: &Path) -> String {
+    Image::open(path)
+        .map(|x| x.data_url())
+        .unwrap_or_else(|_| MISSING_IMAGE.to_string())
+}
+
+/// links that open a page, a mail or a file, not ones that run script
+/// like `javascript:` a workbook could carry. Browsers skip whitespace and
+/// control characters inside a scheme, so they are dropped before the check
+fn is_safe_link(link: &str) -> bool {
+    let link: String = link
+        .chars()
+        .filter(|x| !x.is_ascii_whitespace() && !x.is_ascii_control())
+        .collect();
+    let path = link.find('/').unwrap_or(link.len());
+    match link[..path].split_once(':') {
+        // a one letter scheme is a windows drive like C:
+        Some((scheme, _)) if scheme.len() == 1 => scheme.chars().all(|x| x.is_ascii_alphabetic()),
+        Some((scheme, _)) => ["http", "https", "mailto", "tel", "ftp", "file"]
+            .iter()
+            .any(|x| scheme.eq_ignore_ascii_case(x)),
+        // a relative path
+        None => true,
+    }
+}
 
-    Ok(cards)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_path::TempPath;
+    use calamine::{Cell, Data, Range};
+
+    #[test]
+    fn cards_start_below_the_excel_header_row() {
+        let cells = vec![
+            Cell::new((2, 1), Data::String("logo".into())),
+            Cell::new((4, 1), Data::String("name".into())),
+            Cell::new((4, 2), Data::String("age".into())),
+            Cell::new((5, 1), Data::String("ahmed".into())),
+            Cell::new((5, 2), Data::Int(30)),
+            Cell::new((6, 1), Data::String("mona".into())),
+        ];
+        let sheet = Sheet::new("sheet".into(), Range::from_sparse(cells));
+        let cards = get_cards(
+            &RenderOptions::default(),
+            Header::new(5),
+            &sheet,
+            vec![1, 2, 7],
+            |_, _| true,
+        )
+        .unwrap();
+        let cards: Vec<Vec<(String, String)>> = cards
+            .into_iter()
+            .map(|kvs| kvs.into_iter().map(|x| (x.key, x.value)).collect())
+            .collect();
+        assert_eq!(
+            cards,
+            vec![
+                vec![("name".into(), "ahmed".into()), ("age".into(), "30".into())],
+                vec![("name".into(), "mona".into())],
+            ]
+        );
+    }
+
+    #[test]
+    fn cancelling_stops_the_render() {
+        let sheet = Sheet::new(
+            "sheet".into(),
+            Range::from_sparse(vec![
+                Cell::new((0, 0), Data::String("name".into())),
+                Cell::new((1, 0), Data::String("ahmed".into())),
+            ]),
+        );
+        let result = get_cards(
+            &RenderOptions::default(),
+            Header::new(1),
+            &sheet,
+            vec![0],
+            |_, _| false,
+        );
+        assert!(matches!(result, Err(Error::Cancelled)));
+    }
+
+    #[test]
+    fn linked_cells_become_anchors() {
+        let mut sheet = Sheet::new(
+            "sheet".into(),
+            Range::from_sparse(vec![
+                Cell::new((0, 0), Data::String("site".into())),
+                Cell::new((1, 0), Data::String("home".into())),
+                Cell::new((2, 0), Data::String("trap".into())),
+                Cell::new((3, 0), Data::String("spaced trap".into())),
+                Cell::new((4, 0), Data::String("tabbed trap".into())),
+            ]),
+        );
+        sheet
+            .hyperlinks
+            .insert((1, 0), "https://example.com".into());
+        sheet
+            .hyperlinks
+            .insert((2, 0), "javascript:alert(1)".into());
+        sheet
+            .hyperlinks
+            .insert((3, 0), " javascript:alert(1)".into());
+        sheet
+            .hyperlinks
+            .insert((4, 0), "java\tscript:alert(2)".into());
+        let options = RenderOptions {
+            print_links: true,
+            ..Default::default()
+        };
+        let html = web_cards(&options, Header::new(1), &sheet, vec![0], |_, _| true).unwrap();
+        assert!(html.contains(r#"href="https://example.com""#));
+        assert!(html.contains("(https://example.com)"));
+        assert!(!html.contains("javascript"));
+        assert!(!html.contains("alert"));
+        assert!(is_safe_link("photos/1.png") && is_safe_link(r"C:\docs\a.pdf"));
+        assert!(!is_safe_link("data:text/html,x") && is_safe_link("notes/a:b"));
+    }
+
+    #[test]
+    fn image_columns_show_their_pictures() {
+        let dir = TempPath::new("photos");
+        std::fs::create_dir_all(dir.join("staff")).unwrap();
+        std::fs::write(dir.join("staff/1.png"), b"png").unwrap();
+        std::fs::write(dir.join("staff/notes.txt"), b"secret").unwrap();
+        let sheet = Sheet::new(
+            "sheet".into(),
+            Range::from_sparse(vec![
+                Cell::new((0, 0), Data::String("photo".into())),
+                Cell::new((1, 0), Data::String(r" staff\1.png".into())),
+                Cell::new((2, 0), Data::String("staff/2.png".into())),
+                Cell::new((3, 0), Data::String("staff/notes.txt".into())),
+                Cell::new(
+                    (4, 0),
+                    Data::String(format!(
+                        "../{}/staff/1.png",
+                        dir.file_name().unwrap().to_string_lossy()
+                    )),
+                ),
+                Cell::new(
+                    (5, 0),
+                    Data::String(dir.join("staff/1.png").display().to_string()),
+                ),
+            ]),
+        );
+        let options = RenderOptions {
+            image_columns: ["photo".to_string()].into(),
+            image_dir: dir.to_path_buf(),
+            ..Default::default()
+        };
+        assert_eq!(
+            image_path(&options, r"staff\1.png"),
+            Some(dir.join("staff/1.png"))
+        );
+        let images = |cards: Vec<Card>| -> Vec<_> {
+            cards
+                .into_iter()
+                .map(|x| x[0].image.clone().unwrap())
+                .collect()
+        };
+        let cards = get_cards(&options, Header::new(1), &sheet, vec![0], |_, _| true);
+        let preview = first_cards(&options, Header::new(1), &sheet, &[0], 5);
+        assert_eq!(
+            images(cards.unwrap()),
+            [
+                "data:image/png;base64,cG5n",
+                MISSING_IMAGE,
+                MISSING_IMAGE,
+                MISSING_IMAGE,
+                MISSING_IMAGE
+            ]
+        );
+        assert_eq!(
+            images(preview.unwrap()),
+            [
+                "",
+                MISSING_IMAGE,
+                MISSING_IMAGE,
+                MISSING_IMAGE,
+                MISSING_IMAGE
+            ]
+        );
+    }
 }
@@ -0,0 +1,1577 @@
+use crate::{
+    settings::{DEFAULT_TEMPLATE, Settings, sanitize_file_name},
+    status::{Status, StatusMessage},
+};
+use calamine::Data;
+use iced::{
+    Alignment, Background, Element, Font, Length, Shadow, Task, Theme,
+    border::Radius,
+    event::listen,
+    font,
+    futures::channel::mpsc::{self, UnboundedSender},
+    keyboard::{Key, key::Named},
+    overlay::menu,
+    theme::Palette,
+    widget::{
+        Button, Column, Container, PickList, Row, Scrollable, Text, button, checkbox, column,
+        container, progress_bar, radio, row, scrollable, text,
+        text_input::{self, Style, TextInput},
+    },
+};
+use native_kvg::{
+    error::Error,
+    format::{DEFAULT_DATE_FORMAT, cell_text, is_valid_date_format},
+    formula::FormulaMode,
+    job::{JOB_FILTERS, Job},
+    request::{Column as CardColumn, RenderOptions},
+    sqlite_source::QUERY_SHEET,
+    web_render::{Card, Kv, MISSING_IMAGE, first_cards, web_cards},
+    workbook::{self, Header, Workbook, column_name},
+};
+use rfd::{
+    AsyncFileDialog, AsyncMessageDialog, FileDialog, MessageButtons, MessageDialogResult,
+    MessageLevel,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io, mem,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+};
+
+/// how many cards the preview draws
+const PREVIEW_CARDS: usize = 6;
+/// rows looked at to tell which columns hold dates, numbers, text or formulas
+const SCAN_ROWS: usize = 50;
+/// rows the header grid shows at first and adds on every "more"
+const GRID_ROWS: usize = 30;
+/// the grid stops at column AX, headers further right are rare
+const GRID_COLUMNS: usize = 50;
+
+/// opens the window and blocks until it is closed
+pub fn run() {
+    iced::application(App::new, App::update, App::view)
+        .subscription(App::keyboard_keys)
+        .run()
+        .unwrap();
+}
+
+struct App {
+    card_title: String,
+    excel_path: PathBuf,
+    exel_path_exists: bool,
+    excel_path_auto_complete: Vec<PathBuf>,
+    exel_path_is_excel: bool,
+    workbook: Option<Arc<Workbook>>,
+    all_sheets_names: Arc<[String]>,
+    sheet_name: Option<String>,
+    sql_query: String,
+    grid_rows: usize,
+    title_row_index: Option<usize>,
+    header_rows: usize,
+    fill_merged: bool,
+    print_links: bool,
+    /// the date format of this session, starts from the saved setting and
+    /// an opened job changes it without touching the setting
+    date_format: String,
+    column_date_formats: BTreeMap<String, String>,
+    column_number_formats: BTreeMap<String, String>,
+    column_formula_modes: BTreeMap<String, FormulaMode>,
+    image_columns: BTreeSet<String>,
+    all_titles_names: Vec<(bool, String)>,
+    rendered_at: Option<PathBuf>,
+    status: Status,
+    settings: Settings,
+    busy: Option<Busy>,
+    cancel_render: Arc<AtomicBool>,
+    pending_job: Option<Job>,
+    column_scan: ColumnScan,
+    preview: PreviewCards,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Busy {
+    Loading,
+    Rendering { done: usize, total: usize },
+}
+
+#[derive(Clone)]
+enum Message {
+    CardTitleChanged(String),
+    ExcelPathChanged(PathBuf),
+    SheetNameSelected(String),
+    TitlRowIndexSelected(usize),
+    MoreGridRows,
+    HeaderRowsChanged(usize),
+    PickExelFile,
+    ToggleTitle((usize, bool)),
+    FillMergedToggled(bool),
+    PrintLinksToggled(bool),
+    DateFormatChanged(String),
+    ColumnDateFormatChanged(String, String),
+    ColumnNumberFormatChanged(String, String),
+    ColumnFormulaModeChanged(String, FormulaMode),
+    ImageColumnToggled(String, bool),
+    Render,
+    OutputPicked(Option<PathBuf>),
+    RenderTo(PathBuf),
+    FileTemplateChanged(String),
+    RenderProgress(usize, usize),
+    CancelRender,
+    Rendered(Result<PathBuf, Arc<Error>>),
+    WorkbookLoaded(Result<Arc<Workbook>, Arc<Error>>),
+    SqlQueryChanged(String),
+    RunQuery,
+    QueryRan(Result<Arc<Workbook>, Arc<Error>>),
+    Status(StatusMessage),
+    TabComplete,
+    OpenJob,
+    JobPicked(Option<PathBuf>),
+    SaveJob,
+    JobSaveTo(Option<PathBuf>),
+}
+
+impl App {
+    fn new() -> Self {
+        let settings = Settings::load();
+        Self {
+            card_title: Default::default(),
+            excel_path: Default::default(),
+            exel_path_exists: false,
+            exel_path_is_excel: false,
+            excel_path_auto_complete: Vec::new(),
+            workbook: None,
+            all_sheets_names: Arc::new([]),
+            sheet_name: Default::default(),
+            sql_query: Default::default(),
+            grid_rows: GRID_ROWS,
+            title_row_index: None,
+            header_rows: 1,
+            fill_merged: false,
+            print_links: false,
+            date_format: settings.date_format.clone(),
+            column_date_formats: BTreeMap::new(),
+            column_number_formats: BTreeMap::new(),
+            column_formula_modes: BTreeMap::new(),
+            image_columns: BTreeSet::new(),
+            all_titles_names: Vec::new(),
+            rendered_at: None,
+            status: Status::default(),
+            settings,
+            busy: None,
+            cancel_render: Default::default(),
+            pending_job: None,
+            column_scan: ColumnScan::default(),
+            preview: PreviewCards::default(),
+        }
+    }
+    fn keyboard_keys(&self) -> iced::Subscription<Message> {
+        listen().filter_map(move |event| match event {
+            iced::Event::Keyboard(event) => {
+                if let iced::keyboard::Event::KeyPressed {
+                    key: Key::Named(Named::Tab),
+                    ..
+                } = event
+                {
+                    Some(Message::TabComplete)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        })
+    }
+
+    fn update(&mut self, message: Message) -> Task<Message> {
+        let task = self.handle(message);
+        self.scan_columns();
+        self.preview_cards();
+        task
+    }
+
+    fn handle(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::CardTitleChanged(title) => {
+                self.card_title = title;
+            }
+            Message::ExcelPathChanged(path_buf) => {
+                return self.excel_path_changed(path_buf);
+            }
+            Message::TabComplete => {
+                if let Some(path_buf) = self.excel_path_auto_complete.first() {
+                    return self.excel_path_changed(path_buf.clone());
+                };
+            }
+            Message::WorkbookLoaded(result) => {
+                if self.busy == Some(Busy::Loading) {
+                    self.busy = None;
+                }
+                match result {
+                    Ok(wb) if wb.path == self.excel_path => {
+                        if self
+                            .sheet_name
+                            .as_ref()
+                            .is_some_and(|name| wb.sheet(name).is_none())
+                        {
+                            self.sheet_name = None;
+                        }
+                        self.all_sheets_names = wb.sheet_names().into();
+                        self.workbook = Some(wb);
+                        return self.apply_job();
+                    }
+                    Ok(_) => (),
+                    Err(err) => {
+                        self.pending_job = None;
+                        self.workbook = None;
+                        self.all_sheets_names = Arc::new([]);
+                        self.status.error(self.context(None, None), err);
+                        self.sheet_name = None;
+                    }
+                }
+            }
+            Message::SqlQueryChanged(sql) => {
+                self.sql_query = sql;
+            }
+            Message::RunQuery => {
+                let Some(wb) = self.workbook.clone() else {
+                    return Task::none();
+                };
+                if self.busy.is_some() || self.sql_query.trim().is_empty() {
+                    return Task::none();
+                }
+                let sql = self.sql_query.clone();
+                self.busy = Some(Busy::Loading);
+                return background(move |tx| {
+                    let result = wb.with_query(&sql).map(Arc::new).map_err(Arc::new);
+                    let _ = tx.unbounded_send(Message::QueryRan(result));
+                });
+            }
+            Message::QueryRan(result) => {
+                self.busy = None;
+                match result {
+                    Ok(wb) if wb.path == self.excel_path => {
+                        self.all_sheets_names = wb.sheet_names().into();
+                        self.workbook = Some(wb);
+                        if self.pending_job.is_some() {
+                            return self.apply_job();
+                        }
+                        return Task::done(Message::SheetNameSelected(QUERY_SHEET.to_string()));
+                    }
+                    Ok(_) => (),
+                    Err(err) => {
+                        self.pending_job = None;
+                        self.status
+                            .error(self.context(Some(QUERY_SHEET), None), err);
+                    }
+                }
+            }
+            Message::SheetNameSelected(sheet) => {
+                if let Some(task) = self.reload_then(Message::SheetNameSelected(sheet.clone())) {
+                    return task;
+                }
+                let Some(wb) = &self.workbook else {
+                    return Task::none();
+                };
+                match wb.sheet(&sheet) {
+                    Some(sheet_data) => {
+                        self.grid_rows = GRID_ROWS;
+                        self.sheet_name = Some(sheet);
+                        // only a suggestion, clicking another row in the grid overrides it
+                        if let Some(row) = sheet_data.detect_header_row() {
+                            return Task::done(Message::TitlRowIndexSelected(row));
+                        }
+                    }
+                    None => {
+                        let context = self.context(Some(&sheet), None);
+                        self.status.error(context, Error::SheetNotFound(sheet));
+                    }
+                };
+            }
+            Message::TitlRowIndexSelected(index) => {
+                if let Some(task) = self.reload_then(Message::TitlRowIndexSelected(index)) {
+                    return task;
+                }
+                let Some(sheet) = self.sheet() else {
+                    return Task::none();
+                };
+                let header = Header {
+                    row: index,
+                    rows: self.header_rows,
+                };
+                match sheet.header_titles(header) {
+                    Ok(titles) => {
+                        self.all_titles_names = titles.into_iter().map(|x| (false, x)).collect();
+                        self.title_row_index = Some(index);
+                    }
+                    Err(err) => {
+                        let context = self.context(self.sheet_name.as_deref(), Some(index));
+                        self.status.error(context, err);
+                    }
+                };
+            }
+            Message::HeaderRowsChanged(rows) => {
+                let previous = mem::replace(&mut self.header_rows, rows.max(1));
+                let (Some(sheet), Some(header)) = (self.sheet(), self.header()) else {
+                    return Task::none();
+                };
+                match sheet.header_titles(header) {
+                    // the picked columns stay picked, only their names change
+                    Ok(titles) => {
+                        self.all_titles_names = titles
+                            .into_iter()
+                            .enumerate()
+                            .map(|(index, x)| {
+                                let exists = self.all_titles_names.get(index).is_some_and(|x| x.0);
+                                (exists, x)
+                            })
+                            .collect();
+                    }
+                    Err(err) => {
+                        // the titles shown are still the ones of the old rows
+                        self.header_rows = previous;
+                        let context = self.context(self.sheet_name.as_deref(), Some(header.row));
+                        self.status.error(context, err);
+                    }
+                }
+            }
+            Message::MoreGridRows => {
+                self.grid_rows += GRID_ROWS;
+            }
+            Message::PickExelFile => {
+                if let Some(path) = pick_file() {
+                    return self.excel_path_changed(path);
+                }
+            }
+            Message::ToggleTitle((index, exists)) => {
+                if let Some(title) = self.all_titles_names.get_mut(index) {
+                    title.0 = exists;
+                }
+            }
+            Message::FillMergedToggled(fill) => {
+                self.fill_merged = fill;
+            }
+            Message::PrintLinksToggled(print) => {
+                self.print_links = print;
+            }
+            Message::DateFormatChanged(pattern) => {
+                self.settings.date_format = pattern.clone();
+                self.date_format = pattern;
+            }
+            Message::ColumnDateFormatChanged(header, pattern) => {
+                if pattern.is_empty() {
+                    self.column_date_formats.remove(&header);
+                } else {
+                    self.column_date_formats.insert(header, pattern);
+                }
+            }
+            Message::ColumnNumberFormatChanged(header, code) => {
+                if code.is_empty() {
+                    self.column_number_formats.remove(&header);
+                } else {
+                    self.column_number_formats.insert(header, code);
+                }
+            }
+            Message::ImageColumnToggled(header, image) => {
+                if image {
+                    self.image_columns.insert(header);
+                } else {
+                    self.image_columns.remove(&header);
+                }
+            }
+            Message::ColumnFormulaModeChanged(header, mode) => {
+                if mode == FormulaMode::default() {
+                    self.column_formula_modes.remove(&header);
+                } else {
+                    self.column_formula_modes.insert(header, mode);
+                }
+            }
+            Message::Render => {
+                if self.busy.is_some() {
+                    return Task::none();
+                }
+                let Some(sheet_name) = &self.sheet_name else {
+                    return Task::none();
+                };
+                let file = self
+                    .excel_path
+                    .file_stem()
+                    .map(|x| x.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let file_name = self.settings.file_name(&self.card_title, sheet_name, &file);
+                let mut dialog = AsyncFileDialog::new()
+                    .set_title("حفظ الكروت")
+                    .set_file_name(file_name)
+                    .add_filter("html", &["html"]);
+                if let Some(dir) = self.settings.output_dir() {
+                    dialog = dialog.set_directory(dir);
+                }
+                return Task::perform(dialog.save_file(), |handle| {
+                    Message::OutputPicked(handle.map(|x| x.path().to_path_buf()))
+                });
+            }
+            Message::OutputPicked(None) => (),
+            Message::OutputPicked(Some(path)) => {
+                if !path.exists() {
+                    return Task::done(Message::RenderTo(path));
+                }
+                let confirm = AsyncMessageDialog::new()
+                    .set_level(MessageLevel::Warning)
+                    .set_title("الملف موجود")
+                    .set_description(format!("{} موجود بالفعل، استبداله؟", path.display()))
+                    .set_buttons(MessageButtons::YesNo)
+                    .show();
+                return Task::perform(confirm, move |answer| match answer {
+                    MessageDialogResult::Yes => Message::RenderTo(path.clone()),
+                    _ => Message::Render,
+                });
+            }
+            Message::FileTemplateChanged(template) => {
+                self.settings.file_template = template;
+            }
+            Message::RenderTo(output) => {
+                if self.busy.is_some() {
+                    return Task::none();
+                }
+                if let Some(task) = self.reload_then(Message::RenderTo(output.clone())) {
+                    return task;
+                }
+                self.settings.output_dir = output.parent().map(|x| x.to_path_buf());
+                if let Err(err) = self.settings.save() {
+                    self.status.error("حفظ الاعدادات".to_string(), err);
+                }
+                let (Some(header), Some(sheet_name), Some(wb)) = (
+                    self.header(),
+                    self.sheet_name.clone(),
+                    self.workbook.clone(),
+                ) else {
+                    return Task::none();
+                };
+                let options = self.options();
+                let columns = self
+                    .all_titles_names
+                    .iter()
+                    .enumerate()
+                    .filter(|x| x.1.0)
+                    .map(|x| x.0)
+                    .collect();
+                let cancel = Arc::new(AtomicBool::new(false));
+                self.cancel_render = cancel.clone();
+                self.busy = Some(Busy::Rendering { done: 0, total: 0 });
+                return background(move |tx| {
+                    let mut step = 0;
+                    let result = wb
+                        .sheet(&sheet_name)
+                        .ok_or(Error::SheetNotFound(sheet_name.clone()))
+                        .and_then(|sheet| {
+                            web_cards(&options, header, sheet, columns, |done, total| {
+                                // a message per row would flood the ui, one per percent is plenty
+                                if done >= step || done == total {
+                                    step = done + total / 100;
+                                    let _ = tx.unbounded_send(Message::RenderProgress(done, total));
+                                }
+                                !cancel.load(Ordering::Relaxed)
+                            })
+                        })
+                        .and_then(|html| {
+                            fs::write(&output, html)?;
+                            Ok(output)
+                        })
+                        .map_err(Arc::new);
+                    let _ = tx.unbounded_send(Message::Rendered(result));
+                });
+            }
+            Message::Status(message) => {
+                self.status.update(message);
+            }
+            Message::OpenJob => {
+                let mut dialog = AsyncFileDialog::new()
+                    .set_title("فتح مهمة")
+                    .add_filter("job", &JOB_FILTERS);
+                if let Some(dir) = self.settings.output_dir() {
+                    dialog = dialog.set_directory(dir);
+                }
+                return Task::perform(dialog.pick_file(), |handle| {
+                    Message::JobPicked(handle.map(|x| x.path().to_path_buf()))
+                });
+            }
+            Message::JobPicked(None) => (),
+            Message::JobPicked(Some(path)) => {
+                let job = match Job::load(&path) {
+                    Ok(job) => job,
+                    Err(err) => {
+                        self.status.error(path.display().to_string(), err);
+                        return Task::none();
+                    }
+                };
+                self.card_title = job.title.clone();
+                self.sql_query = job.query.clone().unwrap_or_default();
+                let input = job.input.clone();
+                self.pending_job = Some(job);
+                let task = self.excel_path_changed(input);
+                if self.busy == Some(Busy::Loading) {
+                    return task;
+                }
+                // the workbook was already loaded, nothing will arrive to apply it
+                return self.apply_job();
+            }
+            Message::SaveJob => {
+                let mut dialog = AsyncFileDialog::new()
+                    .set_title("حفظ المهمة")
+                    .set_file_name(job_file_name(&self.card_title))
+                    .add_filter("job", &JOB_FILTERS);
+                if let Some(dir) = self.settings.output_dir() {
+                    dialog = dialog.set_directory(dir);
+                }
+                return Task::perform(dialog.save_file(), |handle| {
+                    Message::JobSaveTo(handle.map(|x| x.path().to_path_buf()))
+                });
+            }
+            Message::JobSaveTo(None) => (),
+            Message::JobSaveTo(Some(path)) => {
+                let context = path.display().to_string();
+                match self.job().save(&path) {
+                    Ok(()) => self.status.info(context, "تم حفظ المهمة"),
+                    Err(err) => self.status.error(context, err),
+                }
+            }
+            Message::RenderProgress(done, total) => {
+                if let Some(Busy::Rendering { .. }) = self.busy {
+                    self.busy = Some(Busy::Rendering { done, total });
+                }
+            }
+            Message::CancelRender => {
+                self.cancel_render.store(true, Ordering::Relaxed);
+            }
+            Message::Rendered(result) => {
+                self.busy = None;
+                match result {
+                    Ok(path) => self.rendered_at = Some(path),
+                    Err(err) if matches!(*err, Error::Cancelled) => {
+                        self.status
+                            .info(self.context(None, None), "تم الغاء التجهيز");
+                    }
+                    Err(err) => {
+                        let context =
+                            self.context(self.sheet_name.as_deref(), self.title_row_index);
+                        self.status.error(context, err);
+                    }
+                }
+            }
+        }
+        Task::none()
+    }
+
+    fn excel_path_changed(&mut self, path_buf: PathBuf) -> Task<Message> {
+        self.exel_path_exists = path_buf.exists();
+        self.exel_path_is_excel = workbook::is_workbook(&path_buf);
+        self.excel_path_auto_complete = path_autocomplete(&path_buf).unwrap_or_default();
+        self.excel_path = path_buf;
+        if self.exel_path_exists && self.exel_path_is_excel {
+            if self
+                .workbook
+                .as_ref()
+                .is_some_and(|wb| wb.path == self.excel_path && !wb.is_stale())
+            {
+                return Task::none();
+            }
+            self.load_workbook()
+        } else {
+            self.workbook = None;
+            self.all_sheets_names = Arc::new([]);
+            self.sheet_name = None;
+            Task::none()
+        }
+    }
+
+    /// the current setup as a job, columns are saved by header name unless
+    /// another column has the same name
+    fn job(&self) -> Job {
+        let query = self
+            .workbook
+            .as_ref()
+            .and_then(|wb| wb.query.clone())
+            .filter(|_| self.sheet_name.as_deref() == Some(QUERY_SHEET));
+        let titles: Vec<String> = self.all_titles_names.iter().map(|x| x.1.clone()).collect();
+        let (mut columns, mut column_letters) = (Vec::new(), Vec::new());
+        for (index, (_, title)) in self
+            .all_titles_names
+            .iter()
+            .enumerate()
+            .filter(|(_, (exists, title))| *exists && !title.is_empty())
+        {
+            match CardColumn::Named(title.clone()).index(&titles) {
+                Ok(found) if found == index => columns.push(title.clone()),
+                _ => column_letters.push(column_name(index)),
+            }
+        }
+        Job {
+            title: self.card_title.clone(),
+            input: self.excel_path.clone(),
+            sheet: self.sheet_name.clone().filter(|_| query.is_none()),
+            query,
+            header_row: self.title_row_index.unwrap_or(1),
+            header_rows: self.header_rows,
+            columns,
+            column_letters,
+            fill_merged: self.fill_merged,
+            print_links: self.print_links,
+            date_format: Some(self.date_format.clone()),
+            column_date_formats: self.column_date_formats.clone(),
+            column_number_formats: self.column_number_formats.clone(),
+            column_formula_modes: self.column_formula_modes.clone(),
+            image_columns: self.image_columns.clone(),
+        }
+    }
+
+    /// puts an opened job on the loaded workbook: query, sheet, header row
+    /// then the columns, a column that is gone is reported and skipped
+    fn apply_job(&mut self) -> Task<Message> {
+        let Some(job) = self.pending_job.take() else {
+            return Task::none();
+        };
+        let Some(wb) = self.workbook.clone() else {
+            self.status
+                .error(self.context(None, None), "تعذر فتح ملف المهمة");
+            return Task::none();
+        };
+        if job.query.is_some() && wb.query != job.query {
+            self.pending_job = Some(job);
+            return self.update(Message::RunQuery);
+        }
+        let request = job.request();
+        let sheet = match request.sheet(&wb) {
+            Ok(sheet) => sheet,
+            Err(err) => {
+                self.status.error(self.context(None, None), err);
+                return Task::none();
+            }
+        };
+        self.grid_rows = GRID_ROWS;
+        self.sheet_name = Some(sheet.name.clone());
+        let header = request.header;
+        let context = self.context(None, Some(header.row));
+        let titles = match sheet.header_titles(header) {
+            Ok(titles) => titles,
+            Err(err) => {
+                self.status.error(context, err);
+                return Task::none();
+            }
+        };
+        self.title_row_index = Some(header.row);
+        self.header_rows = header.rows;
+        self.fill_merged = request.options.fill_merged;
+        self.print_links = request.options.print_links;
+        self.date_format = request.options.date_format.clone();
+        self.column_date_formats = request.options.column_date_formats.clone();
+        self.column_number_formats = request.options.column_number_formats.clone();
+        self.column_formula_modes = request.options.column_formula_modes.clone();
+        self.image_columns = request.options.image_columns.clone();
+        self.all_titles_names = titles.iter().map(|x| (false, x.clone())).collect();
+        for column in &request.columns {
+            match column.index(&titles) {
+                Ok(index) => self.all_titles_names[index].0 = true,
+                Err(err) => self.status.error(context.clone(), err),
+            }
+        }
+        Task::none()
+    }
+
+    fn load_workbook(&mut self) -> Task<Message> {
+        let path = self.excel_path.clone();
+        // a reloaded database gets its last query run again
+        let query = self
+            .workbook
+            .as_ref()
+            .filter(|wb| wb.path == path)
+            .and_then(|wb| wb.query.clone());
+        self.busy = Some(Busy::Loading);
+        background(move |tx| {
+            let result = Workbook::load(&path)
+                .map(|wb| match query.as_deref().map(|sql| wb.with_query(sql)) {
+                    Some(Ok(queried)) => queried,
+                    _ => wb,
+                })
+                .map(Arc::new)
+                .map_err(Arc::new);
+            let _ = tx.unbounded_send(Message::WorkbookLoaded(result));
+        })
+    }
+
+    /// reloads the workbook if it changed on disk then replays `message`
+    /// against the fresh copy
+    fn reload_then(&mut self, message: Message) -> Option<Task<Message>> {
+        if !self.workbook.as_ref().is_some_and(|wb| wb.is_stale()) {
+            return None;
+        }
+        Some(self.load_workbook().chain(Task::done(message)))
+    }
+
+    /// where something happened as the user sees it: file › sheet › row
+    fn context(&self, sheet: Option<&str>, row: Option<usize>) -> String {
+        let file = self
+            .excel_path
+            .file_name()
+            .map(|x| x.to_string_lossy().into_owned());
+        let sheet = sheet
+            .or(self.sheet_name.as_deref())
+            .map(|x| format!("شييت {x}"));
+        let row = row.map(|x| format!("صف {x}"));
+        [file, sheet, row]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" › ")
+    }
+
+    fn options(&self) -> RenderOptions {
+        RenderOptions {
+            title: self.card_title.clone(),
+            fill_merged: self.fill_merged,
+            print_links: self.print_links,
+            date_format: self.date_format.clone(),
+            column_date_formats: self.column_date_formats.clone(),
+            column_number_formats: self.column_number_formats.clone(),
+            column_formula_modes: self.column_formula_modes.clone(),
+            image_columns: self.image_columns.clone(),
+            image_dir: self
+                .excel_path
+                .parent()
+                .map(PathBuf::from)
+                .unwrap_or_default(),
+        }
+    }
+
+    fn header(&self) -> Option<Header> {
+        self.title_row_index.map(|row| Header {
+            row,
+            rows: self.header_rows,
+        })
+    }
+
+    fn sheet(&self) -> Option<&workbook::Sheet> {
+        let name = self.sheet_name.as_ref()?;
+        self.workbook.as_ref()?.sheet(name)
+    }
+
+    /// redoes `column_scan` when the workbook, sheet or header changed
+    fn scan_columns(&mut self) {
+        let key = match (&self.workbook, &self.sheet_name, self.header()) {
+            (Some(wb), Some(name), Some(header)) => {
+                Some((Arc::as_ptr(wb) as usize, name.clone(), header))
+            }
+            _ => None,
+        };
+        if key == self.column_scan.key {
+            return;
+        }
+        self.column_scan = match (self.sheet(), self.header()) {
+            (Some(sheet), Some(header)) => ColumnScan::new(sheet, header),
+            _ => ColumnScan::default(),
+        };
+        self.column_scan.key = key;
+    }
+
+    /// redoes the preview cards when the sheet, header, picked columns or
+    /// options changed
+    fn preview_cards(&mut self) {
+        let columns: Vec<usize> = self
+            .all_titles_names
+            .iter()
+            .enumerate()
+            .filter(|x| x.1.0)
+            .map(|x| x.0)
+            .collect();
+        let key = match (&self.workbook, &self.sheet_name, self.header()) {
+            (Some(wb), Some(name), Some(header)) if !columns.is_empty() => Some((
+                Arc::as_ptr(wb) as usize,
+                name.clone(),
+                header,
+                columns,
+                self.options(),
+            )),
+            _ => None,
+        };
+        if key == self.preview.key {
+            return;
+        }
+        let cards = match (&key, self.sheet()) {
+            (Some((_, _, header, columns, options)), Some(sheet)) => {
+                first_cards(options, *header, sheet, columns, PREVIEW_CARDS).unwrap_or_default()
+            }
+            _ => Vec::new(),
+        };
+        self.preview = PreviewCards { key, cards };
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let ct = self.card_title_view();
+        let et = self.excel_path_view();
+        let sn = self.sheet_name_view();
+        let tri = self.title_row_index_view();
+        let sb = self.submit_button_view();
+        let trp = self.titles_row_pick_view();
+        let nf = self.number_formats_view();
+        let fm = self.formula_modes_view();
+        let ic = self.image_columns_view();
+        let df = self.date_formats_view();
+        let pv = self.preview_view();
+        let st = self.status.view().map(Message::Status);
+        let jb = self.job_view();
+        let col = column![st, jb, ct, et, sn, tri, trp, ic, nf, fm, df, pv, sb]
+            .spacing(25.)
+            .padding(5.)
+            .align_x(Alignment::Center);
+        let col = Scrollable::new(col);
+        Container::new(col)
+            .height(Length::Fill)
+            .padding(80.)
+            .align_x(Alignment::Center)
+            .align_y(Alignment::Center)
+            .style(|theme: &Theme| {
+                let Palette {
+                    background,
+                    text,
+                    primary,
+                    ..
+                } = theme.palette();
+                container::Style {
+                    text_color: Some(text),
+                    background: Some(Background::Color(background)),
+                    border: iced::Border {
+                        color: primary,
+                        width: 7.,
+                        radius: Radius::new(50.),
+                    },
+                    shadow: Shadow::default(),
+                    snap: true,
+                }
+            })
+            .into()
+    }
+
+    fn job_view(&self) -> Element<'_, Message> {
+        let open = Button::new("فتح مهمة").on_press(Message::OpenJob);
+        let save = Button::new("حفظ المهمة")
+            .on_press_maybe(self.title_row_index.is_some().then_some(Message::SaveJob));
+        row![save, open].spacing(10.).into()
+    }
+
+    fn card_title_view(&self) -> Element<'_, Message> {
+        let txt = "عنوان الكارت";
+        let text = (!self.card_title.is_empty()).then_some(Text::new(txt).size(20.));
+        let input = TextInput::new(txt, &self.card_title)
+            .align_x(Alignment::Center)
+            .padding(20.)
+            .size(25.)
+            .style(|theme: &Theme, _| {
+                let Palette {
+                    background,
+                    text,
+                    primary,
+                    warning,
+                    danger,
+                    ..
+                } = theme.palette();
+                Style {
+                    background: Background::Color(background),
+                    border: iced::Border {
+                        color: if self.card_title.is_empty() {
+                            danger
+                        } else {
+                            primary
+                        },
+                        width: 3.,
+                        radius: Radius::new(20.),
+                    },
+                    icon: text,
+                    placeholder: warning,
+                    value: text,
+                    selection: danger,
+                }
+            })
+            .on_input(Message::CardTitleChanged);
+        row![input, text]
+            .align_y(Alignment::Center)
+            .padding(20.)
+            .spacing(20.)
+            .into()
+    }
+    fn excel_path_view(&self) -> Element<'_, Message> {
+        let txt = "موقع ملف الاكسل";
+        let text = (self.excel_path != PathBuf::new()).then_some(Text::new(txt).size(20.));
+        let input = TextInput::new(txt, &self.excel_path.display().to_string())
+            .align_x(Alignment::Center)
+            .padding(10.)
+            .size(25.)
+            .style(|theme: &Theme, _| {
+                let Palette {
+                    background,
+                    text,
+                    primary,
+                    warning,
+                    danger,
+                    ..
+                } = theme.palette();
+                Style {
+                    background: Background::Color(background),
+                    border: iced::Border {
+                        color: primary,
+                        width: 3.,
+                        radius: Radius::new(20.),
+                    },
+                    icon: text,
+                    placeholder: warning,
+                    value: text,
+                    selection: danger,
+                }
+            })
+            .on_input(|x| {
+                let Ok(x) = x.parse();
+                Message::ExcelPathChanged(x)
+            })
+            .style(|th: &Theme, _| {
+                let Palette {
+                    success,
+                    danger,
+                    warning,
+                    primary,
+                    ..
+                } = th.palette();
+                let color = if self.exel_path_exists && self.exel_path_is_excel {
+                    success
+                } else if self.exel_path_exists {
+                    warning
+                } else {
+                    danger
+                };
+                Style {
+                    value: color,
+                    background: iced::Background::Color(iced::Color::WHITE),
+                    border: iced::Border {
+                        color,
+                        width: 3.,
+                        radius: Radius::new(5.),
+                    },
+                    icon: color,
+                    placeholder: color,
+                    selection: primary,
+                }
+            });
+        let browse = Button::new("اختيار ملف").on_press(Message::PickExelFile);
+
+        let ac = self
+            .excel_path_auto_complete
+            .iter()
+            .fold(Row::new(), |acc, path| {
+                acc.push(
+                    Button::new(Text::new(path.display().to_string()))
+                        .on_press(Message::ExcelPathChanged(path.clone())),
+                )
+            })
+            .spacing(5.)
+            .wrap();
+        let input = row![browse, input]
+            .spacing(10.)
+            .spacing(3.)
+            .padding(3.)
+            .align_y(Alignment::Center);
+        let row = row![input, text].spacing(10.).align_y(Alignment::Center);
+        column![row, ac].into()
+    }
+    fn sheet_name_view(&self) -> Element<'_, Message> {
+        let txt = "اسم الشييت";
+        let text = self.sheet_name.as_ref().map(|_| Text::new(txt));
+        let input = PickList::new(
+            self.all_sheets_names.clone(),
+            self.sheet_name.clone(),
+            Message::SheetNameSelected,
+        )
+        .menu_style(|theme: &Theme| {
+            let Palette {
+                background,
+                text,
+                primary,
+                success,
+                ..
+            } = theme.palette();
+            //
+            menu::Style {
+                border: iced::Border {
+                    color: primary,
+                    width: 3.,
+                    radius: Radius::new(3.),
+                },
+                background: Background::Color(background),
+                text_color: text,
+                selected_text_color: success,
+                selected_background: Background::Color(background),
+                shadow: Shadow::default(),
+            }
+        })
+        .text_size(20.)
+        .padding(10.)
+        .placeholder(txt);
+        let picker = row![input, text].align_y(Alignment::Center).spacing(20.);
+        if !self.workbook.as_ref().is_some_and(|wb| wb.is_database()) {
+            return picker.into();
+        }
+        let sql = TextInput::new("SELECT * FROM ...", &self.sql_query)
+            .padding(10.)
+            .size(20.)
+            .on_input(Message::SqlQueryChanged)
+            .on_submit(Message::RunQuery);
+        let run = Button::new("تنفيذ").on_press(Message::RunQuery);
+        let query = row![run, sql, Text::new("او استعلام")]
+            .align_y(Alignment::Center)
+            .spacing(10.);
+        column![picker, query]
+            .spacing(10.)
+            .align_x(Alignment::Center)
+            .into()
+    }
+    /// the first rows of the sheet with their column letters, clicking a row
+    /// makes it the header row
+    fn title_row_index_view(&self) -> Element<'_, Message> {
+        let Some(sheet) = self.sheet() else {
+            return Column::new().into();
+        };
+        let bold = Font {
+            weight: font::Weight::Bold,
+            ..Font::DEFAULT
+        };
+        let cell = |content: String| {
+            Text::new(content)
+                .size(14.)
+                .width(Length::Fixed(110.))
+                .wrapping(text::Wrapping::None)
+        };
+        let number_cell = |content: String| {
+            Text::new(content)
+                .size(14.)
+                .width(Length::Fixed(40.))
+                .font(bold)
+        };
+        let width = sheet.width().min(GRID_COLUMNS);
+        let letters = (0..width)
+            .fold(Row::new().push(number_cell(String::new())), |acc, index| {
+                acc.push(cell(column_name(index)).font(bold))
+            })
+            .padding([0., 5.]);
+        let rows = sheet.row_numbers();
+        let grid =
+            rows.clone()
+                .take(self.grid_rows)
+                .fold(Column::new().push(letters), |acc, number| {
+                    let cells = sheet.row(number).into_iter().take(width).fold(
+                        Row::new().push(number_cell(number.to_string())),
+                        |acc, x| {
+                            let mut content = cell_text(&x, &self.date_format);
+                            if content.chars().count() > 14 {
+                                content = content.chars().take(13).chain(['…']).collect();
+                            }
+                            acc.push(cell(content))
+                        },
+                    );
+                    let selected = self.header().is_some_and(|x| x.contains(number));
+                    acc.push(
+                        Button::new(cells)
+                            .padding([2., 5.])
+                            .style(if selected {
+                                button::primary
+                            } else {
+                                button::text
+                            })
+                            .on_press(Message::TitlRowIndexSelected(number)),
+                    )
+                });
+        let grid = Scrollable::new(grid)
+            .direction(scrollable::Direction::Both {
+                vertical: scrollable::Scrollbar::new(),
+                horizontal: scrollable::Scrollbar::new(),
+            })
+            .height(Length::Fixed(300.));
+        let more = (rows.len() > self.grid_rows)
+            .then(|| Button::new("صفوف اخرى").on_press(Message::MoreGridRows));
+        let txt = match self.header() {
+            Some(Header { row, rows: 1 }) => format!("صف العناوين {row}"),
+            Some(Header { row, rows }) => format!("صفوف العناوين {row} - {}", row + rows - 1),
+            None => "اضغط على صف العناوين".to_string(),
+        };
+        let rows_count = row![
+            Button::new("+").on_press(Message::HeaderRowsChanged(self.header_rows + 1)),
+            Text::new(self.header_rows.to_string()).size(20.),
+            Button::new("-").on_press_maybe(
+                (self.header_rows > 1).then_some(Message::HeaderRowsChanged(self.header_rows - 1))
+            ),
+            Text::new("عدد صفوف العناوين")
+        ]
+        .spacing(10.)
+        .align_y(Alignment::Center);
+        column![Text::new(txt).size(20.), rows_count, grid, more]
+            .spacing(10.)
+            .align_x(Alignment::Center)
+            .into()
+    }
+    fn titles_row_pick_view(&self) -> Element<'_, Message> {
+        let txt = "اختر الاعمدة";
+        let text = (!self.all_titles_names.is_empty()).then_some(Text::new(txt));
+        let titles_row = self
+            .all_titles_names
+            .iter()
+            .enumerate()
+            // indexes are column indexes, so blank columns are kept but not shown
+            .filter(|(_, (_, title))| !title.is_empty())
+            .fold(Row::new(), |acc, (index, (exists, title))| {
+                acc.push(
+                    checkbox(*exists)
+                        .size(20.)
+                        .text_size(20.)
+                        .label(title)
+                        .spacing(20.)
+                        .on_toggle(move |ch| Message::ToggleTitle((index, ch))),
+                )
+            })
+            .spacing(20.)
+            .padding(5.)
+            .align_y(Alignment::Center);
+        let fill_merged = self.sheet().is_some_and(|x| !x.merged.is_empty()).then(|| {
+            checkbox(self.fill_merged)
+                .label("كرر قيمة الخلايا المدمجة في كل كروتها")
+                .on_toggle(Message::FillMergedToggled)
+        });
+        let print_links = self
+            .sheet()
+            .is_some_and(|x| !x.hyperlinks.is_empty())
+            .then(|| {
+                checkbox(self.print_links)
+                    .label("اطبع عنوان الروابط بجانبها")
+                    .on_toggle(Message::PrintLinksToggled)
+            });
+        column![text, titles_row.wrap(), fill_merged, print_links]
+            .spacing(20.)
+            .into()
+    }
+    /// an excel number format for every picked column that has numbers in
+    /// its first rows, left blank the format of each cell is used
+    fn number_formats_view(&self) -> Element<'_, Message> {
+        let formats = self
+            .all_titles_names
+            .iter()
+            .enumerate()
+            .filter(|(_, (exists, title))| *exists && !title.is_empty())
+            .filter_map(|(index, (_, title))| {
+                // the sheet's own format shows through as the placeholder
+                let detected = self.column_scan.numbers.get(&index)?;
+                Some((title, detected.as_deref().unwrap_or("General")))
+            })
+            .fold(Column::new(), |acc, (title, detected)| {
+                let code = self
+                    .column_number_formats
+                    .get(title)
+                    .map(String::as_str)
+                    .unwrap_or_default();
+                let header = title.clone();
+                acc.push(
+                    row![
+                        TextInput::new(detected, code)
+                            .padding(8.)
+                            .size(18.)
+                            .width(Length::Fixed(250.))
+                            .on_input(move |x| Message::ColumnNumberFormatChanged(
+                                header.clone(),
+                                x
+                            )),
+                        Text::new(format!("صيغة ارقام {title}"))
+                    ]
+                    .spacing(10.)
+                    .align_y(Alignment::Center),
+                )
+            })
+            .spacing(5.)
+            .align_x(Alignment::End);
+        column![formats].align_x(Alignment::Center).into()
+    }
+    /// the picked columns that hold text, any of them can be marked as
+    /// holding picture paths
+    fn image_columns_view(&self) -> Element<'_, Message> {
+        let columns: Vec<&String> = self
+            .all_titles_names
+            .iter()
+            .enumerate()
+            .filter(|(_, (exists, title))| *exists && !title.is_empty())
+            .filter(|(index, _)| self.column_scan.texts.contains(index))
+            .map(|(_, (_, title))| title)
+            .collect();
+        if columns.is_empty() {
+            return Column::new().into();
+        }
+        let checks = columns
+            .into_iter()
+            .fold(Row::new(), |acc, title| {
+                let header = title.clone();
+                acc.push(
+                    checkbox(self.image_columns.contains(title))
+                        .label(title)
+                        .on_toggle(move |x| Message::ImageColumnToggled(header.clone(), x)),
+                )
+            })
+            .spacing(20.);
+        row![checks.wrap(), Text::new("اعمدة مسارات الصور")]
+            .spacing(10.)
+            .align_y(Alignment::Center)
+            .into()
+    }
+    /// what formula cells show, for every picked column with formulas in its
+    /// first rows
+    fn formula_modes_view(&self) -> Element<'_, Message> {
+        let label = |mode| match mode {
+            FormulaMode::Cached => "القيمة المحفوظة",
+            FormulaMode::Text => "نص المعادلة",
+            FormulaMode::Evaluate => "احسبها ان لم تحفظ",
+        };
+        let modes = self
+            .all_titles_names
+            .iter()
+            .enumerate()
+            .filter(|(_, (exists, title))| *exists && !title.is_empty())
+            .filter(|(index, _)| self.column_scan.formulas.contains(index))
+            .fold(Column::new(), |acc, (_, (_, title))| {
+                let selected = self.column_formula_modes.get(title).copied();
+                let choices = FormulaMode::ALL.into_iter().fold(Row::new(), |acc, mode| {
+                    let header = title.clone();
+                    acc.push(radio(
+                        label(mode),
+                        mode,
+                        Some(selected.unwrap_or_default()),
+                        move |x| Message::ColumnFormulaModeChanged(header.clone(), x),
+                    ))
+                });
+                acc.push(
+                    row![choices.spacing(15.), Text::new(format!("معادلات {title}"))]
+                        .spacing(10.)
+                        .align_y(Alignment::Center),
+                )
+            })
+            .spacing(5.)
+            .align_x(Alignment::End);
+        column![modes].align_x(Alignment::Center).into()
+    }
+    /// the date pattern for all columns, then one for every picked column
+    /// that has dates in its first rows, a blank one follows the first
+    fn date_formats_view(&self) -> Element<'_, Message> {
+        if self.sheet().is_none() || self.header().is_none() {
+            return Column::new().into();
+        }
+        let pattern_input = |placeholder: &str, pattern: &str| {
+            let valid = pattern.is_empty() || is_valid_date_format(pattern);
+            TextInput::new(placeholder, pattern)
+                .padding(8.)
+                .size(18.)
+                .width(Length::Fixed(250.))
+                .style(move |theme: &Theme, status| {
+                    let mut style = text_input::default(theme, status);
+                    if !valid {
+                        style.border.color = theme.palette().danger;
+                    }
+                    style
+                })
+        };
+        let global = row![
+            pattern_input(DEFAULT_DATE_FORMAT, &self.date_format)
+                .on_input(Message::DateFormatChanged),
+            Text::new("صيغة التاريخ")
+        ]
+        .spacing(10.)
+        .align_y(Alignment::Center);
+        let columns = self
+            .all_titles_names
+            .iter()
+            .enumerate()
+            .filter(|(_, (exists, title))| *exists && !title.is_empty())
+            .filter(|(index, _)| self.column_scan.dates.contains(index))
+            .fold(Column::new(), |acc, (_, (_, title))| {
+                let pattern = self
+                    .column_date_formats
+                    .get(title)
+                    .map(String::as_str)
+                    .unwrap_or_default();
+                let header = title.clone();
+                acc.push(
+                    row![
+                        pattern_input(&self.date_format, pattern)
+                            .on_input(move |x| Message::ColumnDateFormatChanged(header.clone(), x)),
+                        Text::new(title)
+                    ]
+                    .spacing(10.)
+                    .align_y(Alignment::Center),
+                )
+            })
+            .spacing(5.)
+            .align_x(Alignment::End);
+        column![global, columns]
+            .spacing(10.)
+            .align_x(Alignment::Center)
+            .into()
+    }
+
+    /// the first cards drawn the way the page will show them, see
+    /// `preview_cards` for when they are made
+    fn preview_view(&self) -> Element<'_, Message> {
+        if self.preview.cards.is_empty() {
+            return Column::new().into();
+        }
+        let bold = Font {
+            weight: font::Weight::Bold,
+            ..Font::DEFAULT
+        };
+        let cards = self
+            .preview
+            .cards
+            .iter()
+            .fold(Row::new(), |acc, card| {
+                let kvs = card
+                    .iter()
+                    .fold(
+                        Column::new(),
+                        |acc,
+                         Kv {
+                             key,
+                             value,
+                             link,
+                             image,
+                             style,
+                             ..
+                         }| {
+                            let linked = link.is_some();
+                            let font = Font {
+                                weight: if style.bold {
+                                    font::Weight::Bold
+                                } else {
+                                    font::Weight::Normal
+                                },
+                                style: if style.italic {
+                                    font::Style::Italic
+                                } else {
+                                    font::Style::Normal
+                                },
+                                ..Font::DEFAULT
+                            };
+                            let color = style
+                                .color
+                                .as_ref()
+                                .and_then(|x| x.parse::<iced::Color>().ok());
+                            // the page shows the picture itself, the preview only
+                            // marks where it goes
+                            let value = match image.as_deref() {
+                                Some(MISSING_IMAGE) => "[صورة مفقودة]".to_string(),
+                                Some(_) if value.is_empty() => "[صورة]".to_string(),
+                                Some(_) => format!("[صورة] {value}"),
+                                None => value.clone(),
+                            };
+                            let value = match link {
+                                Some(link) if self.print_links => {
+                                    let address = link.strip_prefix("mailto:").unwrap_or(link);
+                                    if address == value {
+                                        value
+                                    } else {
+                                        format!("{value} ({address})")
+                                    }
+                                }
+                                _ => value,
+                            };
+                            acc.push(
+                                row![
+                                    Text::new(value)
+                                        .size(14.)
+                                        .width(Length::Fill)
+                                        .font(font)
+                                        .style(move |theme: &Theme| text::Style {
+                                            color: color
+                                                .or(linked.then(|| theme.palette().primary)),
+                                        }),
+                                    Text::new(key).size(14.).font(bold)
+                                ]
+                                .spacing(8.),
+                            )
+                        },
+                    )
+                    .spacing(4.);
+                let title = Text::new(self.card_title.clone()).size(18.).font(bold);
+                acc.push(
+                    Container::new(column![title, kvs].spacing(8.).align_x(Alignment::Center))
+                        .width(Length::Fixed(260.))
+                        .padding(10.)
+                        .style(|_: &Theme| container::Style {
+                            border: iced::Border {
+                                color: iced::Color::from_rgb8(14, 165, 233),
+                                width: 3.,
+                                radius: Radius::new(12.),
+                            },
+                            ..Default::default()
+                        }),
+                )
+            })
+            .spacing(10.)
+            .wrap();
+        column![Text::new("معاينة"), cards]
+            .spacing(10.)
+            .align_x(Alignment::Center)
+            .into()
+    }
+
+    fn submit_button_view(&self) -> Element<'_, Message> {
+        let clickable =
+            self.all_titles_names.iter().filter(|x| x.0).count() > 0 && !self.card_title.is_empty();
+        let submit = Button::new(if clickable { "تمام" } else { "افندم!" })
+            .on_press_maybe(if clickable {
+                Some(Message::Render)
+            } else {
+                None
+            })
+            .padding(20.);
+        let template = TextInput::new(DEFAULT_TEMPLATE, &self.settings.file_template)
+            .padding(10.)
+            .size(18.)
+            .width(Length::Fixed(400.))
+            .on_input(Message::FileTemplateChanged);
+        let template = row![template, Text::new("اسم ملف الكروت")]
+            .spacing(10.)
+            .align_y(Alignment::Center);
+        let submit = column![template, submit]
+            .spacing(10.)
+            .align_x(Alignment::Center);
+        let rendered_at = self
+            .rendered_at
+            .as_ref()
+            .map(|x| Text::new(format!("rendered at : {}", x.display())));
+        let Some(busy) = self.busy else {
+            return column![submit, rendered_at]
+                .align_x(Alignment::Center)
+                .into();
+        };
+        let busy: Element<'_, Message> = match busy {
+            Busy::Loading => Text::new("جاري تحميل الملف...").into(),
+            Busy::Rendering { done, total } => {
+                let cancel = Button::new("الغاء").on_press(Message::CancelRender);
+                column![
+                    Text::new(format!("جاري تجهيز الكروت {done} / {total}")),
+                    progress_bar(0.0..=total.max(1) as f32, done as f32),
+                    cancel
+                ]
+                .spacing(10.)
+                .align_x(Alignment::Center)
+                .into()
+            }
+        };
+        column![busy, rendered_at]
+            .spacing(10.)
+            .align_x(Alignment::Center)
+            .into()
+    }
+}
+
+/// What the first `SCAN_ROWS` data rows of every column hold, worked out
+/// once per sheet and header so the views offering options for some columns
+/// do not read the sheet on every draw.
+#[derive(Default)]
+struct ColumnScan {
+    /// the workbook (by address), sheet and header it was made for
+    key: Option<(usize, String, Header)>,
+    /// columns with numbers, with the format of the first formatted one
+    numbers: BTreeMap<usize, Option<String>>,
+    dates: BTreeSet<usize>,
+    texts: BTreeSet<usize>,
+    formulas: BTreeSet<usize>,
+}
+
+impl ColumnScan {
+    fn new(sheet: &workbook::Sheet, header: Header) -> Self {
+        let mut scan = Self::default();
+        for number in (header.data_row()..sheet.row_numbers().end).take(SCAN_ROWS) {
+            for (index, value) in sheet.row(number).iter().enumerate() {
+                match value {
+                    Data::Int(_) | Data::Float(_) => {
+                        let format = scan.numbers.entry(index).or_default();
+                        if format.is_none() {
+                            *format = sheet.number_format(number, index).map(String::from);
+                        }
+                    }
+                    Data::DateTime(_) | Data::DateTimeIso(_) | Data::DurationIso(_) => {
+                        scan.dates.insert(index);
+                    }
+                    Data::String(_) => {
+                        scan.texts.insert(index);
+                    }
+                    _ => (),
+                }
+                if sheet.formula(number, index).is_some() {
+                    scan.formulas.insert(index);
+                }
+            }
+        }
+        scan
+    }
+}
+
+/// The first cards of the preview, made in `update` so drawing does not
+/// read the sheet or work out formulas.
+#[derive(Default)]
+struct PreviewCards {
+    /// the workbook (by address), sheet, header, columns and options they
+    /// were made for
+    key: Option<(usize, String, Header, Vec<usize>, RenderOptions)>,
+    cards: Vec<Card>,
+}
+
+/// runs `job` on its own thread, every message it sends is fed back to `update`
+fn background(job: impl FnOnce(UnboundedSender<Message>) + Send + 'static) -> Task<Message> {
+    let (tx, rx) = mpsc::unbounded();
+    thread::spawn(move || job(tx));
+    Task::stream(rx)
+}
+
+/// the name the job save dialog offers, made from the card title
+fn job_file_name(title: &str) -> String {
+    let name = sanitize_file_name(title);
+    let name = if name.is_empty() { "job" } else { &name };
+    format!("{name}.{}", JOB_FILTERS[0])
+}
+
+fn pick_file() -> Option<PathBuf> {
+    FileDialog::new().pick_file()
+}
+
+fn path_autocomplete(path: &PathBuf) -> Result<Vec<PathBuf>, io::Error> {
+    let mut paths = if path.exists() {
+        let mut enteries = fs::read_dir(path)?;
+        let mut paths = Vec::new();
+        while let Some(entry) = enteries.next().transpose()? {
+            paths.push(entry.path());
+        }
+        paths
+    } else if path.parent().is_some_and(|x| x.exists()) {
+        let parent = path.parent().unwrap();
+        let name = path.file_name().unwrap().to_str().unwrap().to_lowercase();
+        let mut enteries = fs::read_dir(parent)?;
+        let mut paths = Vec::new();
+        while let Some(entry) = enteries.next().transpose()? {
+            let epath = entry.path();
+            if epath
+                .file_name()
+                .and_then(|x| x.to_str())
+                .is_some_and(|x| x.to_lowercase().starts_with(&name))
+            {
+                paths.push(epath);
+            }
+        }
+        paths
+    } else {
+        Vec::new()
+    };
+    paths.sort();
+    Ok(paths)
+}
@@ -0,0 +1,694 @@
+use crate::{
+    csv_source::{self, CSV_FILTERS},
+    error::Error,
+    job::JOB_FILTERS,
+    json_source::{self, JSON_FILTERS},
+    sqlite_source::{self, QUERY_SHEET, SQLITE_FILTERS},
+    xlsx_parts::{
+        Hyperlinks, Image, Images, NumberFormats, RichTexts, Run, TextStyle, TextStyles, XlsxParts,
+    },
+};
+use calamine::{
+    Data, Dimensions, Ods, Range, Reader, Sheets, Xls, Xlsb, Xlsx, open_workbook,
+    open_workbook_auto,
+};
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+    time::SystemTime,
+};
+
+pub const XLSX_FILTERS: [&str; 4] = ["xls", "xlsx", "xlsb", "ods"];
+
+/// true for the files cards can be made from, job files are not among them
+pub fn is_workbook(path: &Path) -> bool {
+    extension(path).is_some_and(|x| {
+        let x = x.as_str();
+        !JOB_FILTERS.contains(&x)
+            && (XLSX_FILTERS.contains(&x)
+                || CSV_FILTERS.contains(&x)
+                || JSON_FILTERS.contains(&x)
+                || SQLITE_FILTERS.contains(&x))
+    })
+}
+
+pub fn open(path: &Path) -> Result<Sheets<BufReader<File>>, calamine::Error> {
+    use calamine::Error;
+    let wb = match extension(path).as_deref() {
+        Some("xls") => Sheets::Xls(open_workbook::<Xls<_>, _>(path).map_err(Error::Xls)?),
+        Some("xlsx") => Sheets::Xlsx(open_workbook::<Xlsx<_>, _>(path).map_err(Error::Xlsx)?),
+        Some("xlsb") => Sheets::Xlsb(open_workbook::<Xlsb<_>, _>(path).map_err(Error::Xlsb)?),
+        Some("ods") => Sheets::Ods(open_workbook::<Ods<_>, _>(path).map_err(Error::Ods)?),
+        _ => open_workbook_auto(path)?,
+    };
+    Ok(wb)
+}
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|x| x.to_str())
+        .map(|x| x.to_lowercase())
+}
+
+/// excel column letters of a column index, 0 is `A` and 26 is `AA`
+pub fn column_name(index: usize) -> String {
+    let mut index = index + 1;
+    let mut name = Vec::new();
+    while index > 0 {
+        let rem = (index - 1) % 26;
+        name.push(b'A' + rem as u8);
+        index = (index - 1) / 26;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// the column index of excel column letters, case insensitive
+pub fn column_index(name: &str) -> Option<usize> {
+    if name.is_empty() || name.len() > 3 || !name.chars().all(|x| x.is_ascii_alphabetic()) {
+        return None;
+    }
+    let index = name
+        .to_ascii_uppercase()
+        .bytes()
+        .fold(0, |acc, x| acc * 26 + (x - b'A') as usize + 1);
+    Some(index - 1)
+}
+
+/// finds a column by its header first and by its letters second, so a header
+/// that happens to look like letters still wins
+pub fn find_column(titles: &[String], spec: &str) -> Result<usize, Error> {
+    let spec = spec.trim();
+    titles
+        .iter()
+        .position(|x| x.trim() == spec)
+        .or_else(|| column_index(spec))
+        .ok_or_else(|| Error::ColumnNotFound(spec.to_string()))
+}
+
+/// the merged areas of a sheet, the readers other than xlsx and xls do not
+/// know about them
+fn merged_cells(
+    wb: &mut Sheets<BufReader<File>>,
+    name: &str,
+) -> Result<Vec<Dimensions>, calamine::Error> {
+    let merged = match wb {
+        Sheets::Xlsx(x) => x
+            .worksheet_merge_cells(name)
+            .transpose()
+            .map_err(calamine::Error::Xlsx)?,
+        Sheets::Xls(x) => x.worksheet_merge_cells(name),
+        _ => None,
+    };
+    Ok(merged.unwrap_or_default())
+}
+
+/// what calamine leaves out of an xlsx: number formats, links, pictures and
+/// fonts. They only dress the values up, so a part that is missing or broken
+/// is left out instead of failing the whole workbook
+fn read_xlsx_parts(path: &Path, sheets: &mut [Sheet]) {
+    let Ok(mut parts) = XlsxParts::open(path) else {
+        return;
+    };
+    let mut found = parts.sheets();
+    for sheet in sheets {
+        let Some(parts) = found.remove(&sheet.name) else {
+            continue;
+        };
+        sheet.number_formats = parts.number_formats;
+        sheet.hyperlinks = parts.hyperlinks;
+        sheet.images = parts.images;
+        sheet.text_styles = parts.text_styles;
+        sheet.rich_texts = parts.rich_texts;
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|x| x.modified()).ok()
+}
+
+/// A workbook parsed once and kept in memory, every sheet included.
+#[derive(Clone)]
+pub struct Workbook {
+    pub path: PathBuf,
+    modified: Option<SystemTime>,
+    /// shared so a copy of the workbook does not copy every sheet
+    sheets: Vec<Arc<Sheet>>,
+    /// the query behind the `QUERY_SHEET` of a database, if any
+    pub query: Option<String>,
+}
+
+#[derive(Clone, Default)]
+pub struct Sheet {
+    pub name: String,
+    pub range: Range<Data>,
+    /// merged cell areas, only xlsx and xls files have them
+    pub merged: Vec<Dimensions>,
+    /// number format codes of the cells, only read from xlsx files
+    pub number_formats: NumberFormats,
+    /// formulas of the cells without their `=`, read from `formula_file` the
+    /// first time they are asked for, empty for files that have none
+    pub(crate) formulas: OnceLock<Range<String>>,
+    /// the workbook the formulas are in, so the sheets nobody looks at are
+    /// not parsed a second time for them
+    formula_file: Option<PathBuf>,
+    /// link targets of the cells, only read from xlsx files
+    pub hyperlinks: Hyperlinks,
+    /// pictures anchored to the cells, only read from xlsx files
+    pub images: Images,
+    /// fonts of the cells that are not in the plain font, only read from
+    /// xlsx files
+    pub text_styles: TextStyles,
+    /// runs of the cells with differently styled pieces of text, only read
+    /// from xlsx files
+    pub rich_texts: RichTexts,
+}
+
+impl Workbook {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let modified = modified(path);
+        let sheets = match extension(path) {
+            Some(x) if CSV_FILTERS.contains(&x.as_str()) => {
+                vec![Sheet::single(path, csv_source::load(path)?)]
+            }
+            Some(x) if JSON_FILTERS.contains(&x.as_str()) => {
+                vec![Sheet::single(path, json_source::load(path)?)]
+            }
+            Some(x) if SQLITE_FILTERS.contains(&x.as_str()) => sqlite_source::load(path)?
+                .into_iter()
+                .map(|(name, range)| Sheet::new(name, range))
+                .collect(),
+            ext => {
+                let mut wb = open(path)?;
+                let mut sheets = wb
+                    .sheet_names()
+                    .into_iter()
+                    .map(|name| {
+                        let range = wb.worksheet_range(&name)?;
+                        let merged = merged_cells(&mut wb, &name)?;
+                        Ok(Sheet {
+                            name,
+                            range,
+                            merged,
+                            formula_file: Some(path.to_path_buf()),
+                            ..Default::default()
+                        })
+                    })
+                    .collect::<Result<Vec<_>, calamine::Error>>()?;
+                if matches!(ext.as_deref(), Some("xlsx" | "xlsm")) {
+                    read_xlsx_parts(path, &mut sheets);
+                }
+                sheets
+            }
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            modified,
+            sheets: sheets.into_iter().map(Arc::new).collect(),
+            query: None,
+        })
+    }
+
+    pub fn is_database(&self) -> bool {
+        extension(&self.path).is_some_and(|x| SQLITE_FILTERS.contains(&x.as_str()))
+    }
+
+    /// a copy of this database with the result of `sql` as its `QUERY_SHEET`
+    pub fn with_query(&self, sql: &str) -> Result<Self, Error> {
+        let range = sqlite_source::query(&self.path, sql)?;
+        let mut wb = self.clone();
+        wb.sheets.retain(|x| x.name != QUERY_SHEET);
+        wb.sheets
+            .push(Arc::new(Sheet::new(QUERY_SHEET.to_string(), range)));
+        wb.query = Some(sql.to_string());
+        Ok(wb)
+    }
+
+    /// true when the file on disk was touched after it got loaded
+    pub fn is_stale(&self) -> bool {
+        modified(&self.path) != self.modified
+    }
+
+    pub fn sheet_names(&self) -> Vec<String> {
+        self.sheets.iter().map(|x| x.name.clone()).collect()
+    }
+
+    pub fn sheet(&self, name: &str) -> Option<&Sheet> {
+        self.sheets.iter().find(|x| x.name == name).map(Arc::as_ref)
+    }
+}
+
+impl Sheet {
+    pub fn new(name: String, range: Range<Data>) -> Self {
+        Self {
+            name,
+            range,
+            ..Default::default()
+        }
+    }
+
+    /// the lone sheet of a plain data file, named after the file
+    fn single(path: &Path, range: Range<Data>) -> Self {
+        let name = path
+            .file_stem()
+            .map(|x| x.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        Self::new(name, range)
+    }
+
+    /// row numbers as excel shows them: 1 based, leading blank rows counted
+    pub fn row_numbers(&self) -> std::ops::Range<usize> {
+        match (self.range.start(), self.range.end()) {
+            (Some(start), Some(end)) => (start.0 as usize + 1)..(end.0 as usize + 2),
+            _ => 0..0,
+        }
+    }
+
+    /// number of columns counting from column A up to the last used one
+    pub fn width(&self) -> usize {
+        self.range
+            .end()
+            .map(|x| x.1 as usize + 1)
+            .unwrap_or_default()
+    }
+
+    /// the cells of row `number` (excel numbering) from column A onwards so
+    /// that the index of a cell is its column index, anything outside the
+    /// used range is `Data::Empty`
+    pub fn row(&self, number: usize) -> Vec<Data> {
+        let row = number.saturating_sub(1) as u32;
+        (0..self.width() as u32)
+            .map(|col| {
+                self.range
+                    .get_value((row, col))
+                    .cloned()
+                    .unwrap_or(Data::Empty)
+            })
+            .collect()
+    }
+
+    /// like `row` but every cell of a merged area holds the value of its top
+    /// left cell, which is where excel keeps it
+    pub fn filled_row(&self, number: usize) -> Vec<Data> {
+        let mut row = self.row(number);
+        let r = number.saturating_sub(1) as u32;
+        for area in self
+            .merged
+            .iter()
+            .filter(|x| x.start.0 <= r && r <= x.end.0)
+        {
+            let value = self
+                .range
+                .get_value(area.start)
+                .cloned()
+                .unwrap_or(Data::Empty);
+            for col in area.start.1..=area.end.1 {
+                if let Some(cell) = row.get_mut(col as usize) {
+                    *cell = value.clone();
+                }
+            }
+        }
+        row
+    }
+
+    /// the number format the workbook gives the cell at row `number` (excel
+    /// numbering) and column `index`
+    pub fn number_format(&self, number: usize, index: usize) -> Option<&str> {
+        let row = number.checked_sub(1)? as u32;
+        self.number_formats
+            .get(&(row, index as u32))
+            .map(String::as_str)
+    }
+
+    /// where the cell at row `number` (excel numbering) and column `index`
+    /// links to
+    pub fn hyperlink(&self, number: usize, index: usize) -> Option<&str> {
+        let row = number.checked_sub(1)? as u32;
+        self.hyperlinks
+            .get(&(row, index as u32))
+            .map(String::as_str)
+    }
+
+    /// the picture sitting in the cell at row `number` (excel numbering) and
+    /// column `index`
+    pub fn image(&self, number: usize, index: usize) -> Option<&Image> {
+        let row = number.checked_sub(1)? as u32;
+        self.images.get(&(row, index as u32))
+    }
+
+    /// the font of the cell at row `number` (excel numbering) and column
+    /// `index`, `None` for the plain font
+    pub fn text_style(&self, number: usize, index: usize) -> Option<&TextStyle> {
+        let row = number.checked_sub(1)? as u32;
+        self.text_styles.get(&(row, index as u32))
+    }
+
+    /// the styled runs of the cell at row `number` (excel numbering) and
+    /// column `index`, `None` when its text is all in one font
+    pub fn runs(&self, number: usize, index: usize) -> Option<&[Run]> {
+        let row = number.checked_sub(1)? as u32;
+        self.rich_texts.get(&(row, index as u32)).map(Vec::as_slice)
+    }
+
+    /// the formulas of the sheet, read from the workbook on first use.
+    /// Formulas are extra, a sheet whose formulas calamine cannot read still
+    /// shows its values
+    pub(crate) fn formulas(&self) -> &Range<String> {
+        self.formulas.get_or_init(|| {
+            self.formula_file
+                .as_deref()
+                .and_then(|path| open(path).ok()?.worksheet_formula(&self.name).ok())
+                .unwrap_or_default()
+        })
+    }
+
+    /// the formula of the cell at row `number` (excel numbering) and column
+    /// `index`, without its `=`
+    pub fn formula(&self, number: usize, index: usize) -> Option<&str> {
+        let row = number.checked_sub(1)? as u32;
+        self.formulas()
+            .get_value((row, index as u32))
+            .map(String::as_str)
+            .filter(|x| !x.is_empty())
+    }
+
+    /// the titles of row `number`, a merged title counts for all its columns
+    pub fn titles(&self, number: usize) -> Result<Vec<String>, Error> {
+        if !self.row_numbers().contains(&number) {
+            return Err(Error::RowOutOfRange(number));
+        }
+        Ok(self
+            .filled_row(number)
+            .iter()
+            .map(|x| x.to_string())
+            .collect())
+    }
+
+    /// one title per column for a header of several rows, the labels of a
+    /// column are joined like `Address / City`. Merged cells say which
+    /// columns a group label covers, a sheet without any gets each group
+    /// label carried right over the blank cells next to it as long as the
+    /// groups above it carry on too and there is a label under it
+    pub fn header_titles(&self, header: Header) -> Result<Vec<String>, Error> {
+        if header.rows <= 1 {
+            return self.titles(header.row);
+        }
+        let mut rows = (header.row..header.data_row())
+            .map(|number| {
+                self.titles(number)
+                    .map(|x| x.into_iter().map(|x| x.trim().to_string()).collect())
+            })
+            .collect::<Result<Vec<Vec<String>>, Error>>()?;
+        let guess_groups = self.merged.is_empty();
+        for r in 0..rows.len() - 1 {
+            for c in 1..self.width() {
+                let same_group = (0..r).all(|above| rows[above][c] == rows[above][c - 1]);
+                let labelled_below = rows[r + 1..].iter().any(|x| !x[c].is_empty());
+                if guess_groups && rows[r][c].is_empty() && same_group && labelled_below {
+                    rows[r][c] = rows[r][c - 1].clone();
+                }
+            }
+        }
+        let titles = (0..self.width())
+            .map(|c| {
+                let mut labels: Vec<&str> = Vec::new();
+                for row in rows.iter() {
+                    let label = row[c].as_str();
+                    if !label.is_empty() && labels.last() != Some(&label) {
+                        labels.push(label);
+                    }
+                }
+                labels.join(" / ")
+            })
+            .collect();
+        Ok(titles)
+    }
+
+    /// a guess of the header row: the first row that is mostly filled with
+    /// distinct text and has data right under it, title and logo rows above
+    /// the real header fill too few cells to pass
+    pub fn detect_header_row(&self) -> Option<usize> {
+        let rows = self.row_numbers();
+        let filled = |number: usize| {
+            self.row(number)
+                .iter()
+                .filter(|x| !x.to_string().trim().is_empty())
+                .count()
+        };
+        let widest = rows
+            .clone()
+            .take(HEADER_SCAN_ROWS)
+            .map(filled)
+            .max()
+            .filter(|x| *x > 0)?;
+        let is_header = |number: usize| {
+            let row = self.row(number);
+            let texts: Vec<String> = row
+                .iter()
+                .map(|x| x.to_string().trim().to_string())
+                .filter(|x| !x.is_empty())
+                .collect();
+            let all_text = row
+                .iter()
+                .all(|x| matches!(x, Data::Empty | Data::String(_)));
+            let distinct = texts.iter().collect::<HashSet<_>>().len() == texts.len();
+            all_text
+                && distinct
+                && texts.len() >= widest.min(2)
+                && texts.len() * 2 >= widest
+                && rows.contains(&(number + 1))
+                && filled(number + 1) > 0
+        };
+        rows.clone()
+            .take(HEADER_SCAN_ROWS)
+            .find(|x| is_header(*x))
+            .or_else(|| rows.clone().find(|x| filled(*x) > 0))
+    }
+}
+
+/// The header rows of a sheet, `row` is the excel number of the top one and
+/// `rows` how many there are, more than one for grouped headers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Header {
+    pub row: usize,
+    pub rows: usize,
+}
+
+impl Header {
+    pub fn new(row: usize) -> Self {
+        Self { row, rows: 1 }
+    }
+
+    /// the first row under the header
+    pub fn data_row(&self) -> usize {
+        self.row + self.rows.max(1)
+    }
+
+    pub fn contains(&self, number: usize) -> bool {
+        (self.row..self.data_row()).contains(&number)
+    }
+}
+
+impl Default for Header {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+/// header detection only looks this far down
+const HEADER_SCAN_ROWS: usize = 20;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp_path::TempPath;
+    use calamine::Cell;
+
+    #[test]
+    fn job_files_are_not_workbooks() {
+        assert!(is_workbook(Path::new("people.json")));
+        assert!(is_workbook(Path::new("people.XLSX")));
+        assert!(!is_workbook(Path::new("people.kvgjob")));
+        assert!(!is_workbook(Path::new("people")));
+    }
+
+    /// a sheet whose used range starts at C5, like one with a logo above it
+    fn offset_sheet() -> Sheet {
+        let cells = vec![
+            Cell::new((4, 2), Data::String("name".into())),
+            Cell::new((4, 3), Data::String("age".into())),
+            Cell::new((5, 2), Data::String("ahmed".into())),
+            Cell::new((5, 3), Data::Int(30)),
+            Cell::new((7, 3), Data::Int(40)),
+        ];
+        Sheet::new("sheet".into(), Range::from_sparse(cells))
+    }
+
+    #[test]
+    fn row_numbers_match_excel() {
+        let sheet = offset_sheet();
+        assert_eq!(sheet.row_numbers(), 5..9);
+        assert_eq!(sheet.width(), 4);
+    }
+
+    #[test]
+    fn titles_are_read_from_the_excel_row() {
+        let sheet = offset_sheet();
+        assert_eq!(sheet.titles(5).unwrap(), ["", "", "name", "age"]);
+        assert_eq!(sheet.row(6)[2], Data::String("ahmed".into()));
+        assert_eq!(sheet.row(7), vec![Data::Empty; 4]);
+    }
+
+    #[test]
+    fn rows_outside_the_sheet_are_not_titles() {
+        let sheet = offset_sheet();
+        assert!(sheet.titles(4).is_err());
+        assert!(sheet.titles(9).is_err());
+    }
+
+    #[test]
+    fn header_is_found_under_the_title_rows() {
+        let cells = vec![
+            Cell::new((0, 0), Data::String("monthly report".into())),
+            Cell::new((1, 2), Data::String("2024".into())),
+            Cell::new((3, 0), Data::String("id".into())),
+            Cell::new((3, 1), Data::String("name".into())),
+            Cell::new((3, 2), Data::String("age".into())),
+            Cell::new((4, 0), Data::Int(1)),
+            Cell::new((4, 1), Data::String("ahmed".into())),
+            Cell::new((4, 2), Data::Int(30)),
+        ];
+        let sheet = Sheet::new("sheet".into(), Range::from_sparse(cells));
+        assert_eq!(sheet.detect_header_row(), Some(4));
+        assert_eq!(offset_sheet().detect_header_row(), Some(5));
+    }
+
+    #[test]
+    fn grouped_headers_are_joined() {
+        let text = |row, col, x: &str| Cell::new((row, col), Data::String(x.into()));
+        let cells = vec![
+            text(0, 0, "name"),
+            text(0, 1, "address"),
+            text(1, 1, "city"),
+            text(1, 2, "street"),
+            text(0, 3, "phone"),
+            text(2, 0, "ahmed"),
+        ];
+        let sheet = Sheet::new("sheet".into(), Range::from_sparse(cells));
+        let header = Header { row: 1, rows: 2 };
+        assert_eq!(
+            sheet.header_titles(header).unwrap(),
+            ["name", "address / city", "address / street", "phone"]
+        );
+        assert_eq!(header.data_row(), 3);
+        assert!(sheet.header_titles(Header { row: 3, rows: 2 }).is_err());
+    }
+
+    #[test]
+    fn merged_cells_fill_their_area() {
+        let text = |row, col, x: &str| Cell::new((row, col), Data::String(x.into()));
+        let cells = vec![
+            text(0, 0, "name"),
+            text(0, 1, "address"),
+            text(1, 1, "city"),
+            text(1, 2, "street"),
+            text(2, 1, "cairo"),
+        ];
+        let area = |start, end| Dimensions { start, end };
+        let sheet = Sheet {
+            merged: vec![
+                area((0, 0), (1, 0)),
+                area((0, 1), (0, 2)),
+                area((2, 1), (3, 1)),
+            ],
+            ..Sheet::new("sheet".into(), Range::from_sparse(cells))
+        };
+        assert_eq!(
+            sheet.header_titles(Header { row: 1, rows: 2 }).unwrap(),
+            ["name", "address / city", "address / street"]
+        );
+        assert_eq!(sheet.row(4)[1], Data::Empty);
+        assert_eq!(sheet.filled_row(4)[1], Data::String("cairo".into()));
+    }
+
+    #[test]
+    fn column_letters_round_trip() {
+        for (index, name) in [(0, "A"), (25, "Z"), (26, "AA"), (701, "ZZ"), (702, "AAA")] {
+            assert_eq!(column_name(index), name);
+            assert_eq!(column_index(name), Some(index));
+        }
+        assert_eq!(column_index("c"), Some(2));
+        assert_eq!(column_index("A1"), None);
+        assert_eq!(column_index(""), None);
+    }
+
+    #[test]
+    fn empty_sheet_has_no_rows() {
+        let sheet = Sheet::new("empty".into(), Range::empty());
+        assert!(sheet.row_numbers().is_empty());
+        assert!(sheet.titles(1).is_err());
+        assert_eq!(sheet.detect_header_row(), None);
+    }
+
+    #[test]
+    fn a_broken_drawing_keeps_the_values() {
+        use std::io::Write;
+        const RELS: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+        let path = TempPath::new("broken.xlsx");
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        let mut part = |name: &str, xml: String| {
+            zip.start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(xml.as_bytes()).unwrap();
+        };
+        let relationships = |x: &str| {
+            format!(
+                r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{x}</Relationships>"#
+            )
+        };
+        part(
+            "[Content_Types].xml",
+            r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/></Types>"#.into(),
+        );
+        part(
+            "_rels/.rels",
+            relationships(&format!(
+                r#"<Relationship Id="rId1" Type="{RELS}/officeDocument" Target="xl/workbook.xml"/>"#
+            )),
+        );
+        part(
+            "xl/workbook.xml",
+            format!(
+                r#"<workbook xmlns:r="{RELS}"><sheets><sheet name="Sheet1" sheetId="1" r:id="rId1"/></sheets></workbook>"#
+            ),
+        );
+        part(
+            "xl/_rels/workbook.xml.rels",
+            relationships(&format!(
+                r#"<Relationship Id="rId1" Type="{RELS}/worksheet" Target="worksheets/sheet1.xml"/>"#
+            )),
+        );
+        part(
+            "xl/worksheets/sheet1.xml",
+            format!(
+                r#"<worksheet xmlns:r="{RELS}"><sheetData><row r="1"><c r="A1"><v>7</v></c></row></sheetData><drawing r:id="rId1"/></worksheet>"#
+            ),
+        );
+        part(
+            "xl/worksheets/_rels/sheet1.xml.rels",
+            relationships(&format!(
+                r#"<Relationship Id="rId1" Type="{RELS}/drawing" Target="../drawings/drawing1.xml"/>"#
+            )),
+        );
+        part("xl/drawings/drawing1.xml", "<wsDr><a></b></wsDr>".into());
+        zip.finish().unwrap();
+
+        let workbook = Workbook::load(&path);
+        let sheet = workbook.unwrap().sheets.remove(0);
+        assert_eq!(sheet.row(1), vec![Data::Float(7.)]);
+        assert!(sheet.images.is_empty());
+    }
+}
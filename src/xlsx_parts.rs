@@ -0,0 +1,776 @@
+use crate::{error::Error, workbook::column_index};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use quick_xml::{
+    Reader,
+    escape::unescape,
+    events::{BytesStart, Event},
+};
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+    sync::Arc,
+};
+use zip::{ZipArchive, result::ZipError};
+
+/// number format codes of a sheet by (row, column), counted from 0 like
+/// calamine does, cells in the General format are left out
+pub type NumberFormats = HashMap<(u32, u32), String>;
+
+/// hyperlink targets of a sheet by (row, column), counted from 0
+pub type Hyperlinks = HashMap<(u32, u32), String>;
+
+/// pictures of a sheet by the (row, column) their top left corner sits in,
+/// counted from 0
+pub type Images = HashMap<(u32, u32), Image>;
+
+/// the index into the cell styles of every styled cell of a sheet by
+/// (row, column), counted from 0
+type CellStyles = HashMap<(u32, u32), usize>;
+
+/// font styles of a sheet by (row, column), counted from 0, cells in the
+/// plain font are left out
+pub type TextStyles = HashMap<(u32, u32), TextStyle>;
+
+/// the runs of the rich text cells of a sheet by (row, column), counted
+/// from 0
+pub type RichTexts = HashMap<(u32, u32), Vec<Run>>;
+
+/// How the text of a cell or a run of it is drawn.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub strike: bool,
+    /// a css color like `#FF0000`, `None` for the default text color
+    pub color: Option<String>,
+}
+
+impl TextStyle {
+    pub fn is_plain(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// the style as an inline css declaration list
+    pub fn css(&self) -> String {
+        let mut css = format!(
+            "font-weight: {}; font-style: {}; text-decoration: {}",
+            if self.bold { "bold" } else { "normal" },
+            if self.italic { "italic" } else { "normal" },
+            if self.strike { "line-through" } else { "none" },
+        );
+        if let Some(color) = &self.color {
+            css.push_str(&format!("; color: {color}"));
+        }
+        css
+    }
+
+    /// takes in one child of a `<font>` or `<rPr>`
+    fn apply(&mut self, e: &BytesStart) -> Result<(), Error> {
+        // `<b/>` is on, `<b val="0"/>` is off
+        let on = || -> Result<bool, Error> {
+            Ok(!matches!(
+                attribute(e, b"val")?.as_deref(),
+                Some("0" | "false")
+            ))
+        };
+        match e.local_name().as_ref() {
+            b"b" => self.bold = on()?,
+            b"i" => self.italic = on()?,
+            b"strike" => self.strike = on()?,
+            b"color" => self.color = color(e)?,
+            _ => (),
+        }
+        Ok(())
+    }
+}
+
+/// A piece of a rich text cell.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Run {
+    pub text: String,
+    /// `None` when the run takes the font of its cell
+    pub style: Option<TextStyle>,
+}
+
+/// A picture file as it is stored in the package.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub mime: &'static str,
+    /// shared so copies of a sheet do not copy the picture
+    pub data: Arc<[u8]>,
+}
+
+impl Image {
+    /// a picture file on disk, files without a picture extension are
+    /// refused so a sheet cannot pull any file into the page
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        let mime = image_mime(&path.to_string_lossy())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a picture file"))?;
+        Ok(Self {
+            mime,
+            data: std::fs::read(path)?.into(),
+        })
+    }
+
+    /// the picture as a `data:` url an `<img>` can show without the file
+    pub fn data_url(&self) -> String {
+        format!("data:{};base64,{}", self.mime, STANDARD.encode(&self.data))
+    }
+}
+
+/// What calamine leaves out of one sheet.
+#[derive(Default)]
+pub struct SheetParts {
+    pub number_formats: NumberFormats,
+    pub hyperlinks: Hyperlinks,
+    pub images: Images,
+    pub text_styles: TextStyles,
+    pub rich_texts: RichTexts,
+}
+
+/// The parts of an xlsx package calamine does not hand out, read straight
+/// from the xml inside the zip.
+pub struct XlsxParts {
+    zip: ZipArchive<BufReader<File>>,
+    /// sheet names with the path of their part, in workbook order
+    sheets: Vec<(String, String)>,
+}
+
+impl XlsxParts {
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        let zip = ZipArchive::new(BufReader::new(File::open(path)?))?;
+        let mut parts = Self {
+            zip,
+            sheets: Vec::new(),
+        };
+        let relationships = parts.relationships("xl/workbook.xml")?;
+        let workbook = parts.bytes("xl/workbook.xml")?.unwrap_or_default();
+        let mut reader = Reader::from_reader(workbook.as_slice());
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"sheet" => {
+                    let name = attribute(&e, b"name")?;
+                    let id = attribute(&e, b"id")?;
+                    if let (Some(name), Some(path)) = (name, id.and_then(|x| relationships.get(&x)))
+                    {
+                        parts.sheets.push((name, path.clone()));
+                    }
+                }
+                Event::Eof => break,
+                _ => (),
+            }
+        }
+        Ok(parts)
+    }
+
+    /// the number formats, fonts, links, pictures and rich texts of every
+    /// sheet, by sheet name. Every sheet is read once, a part that is
+    /// missing or broken leaves what it would have added empty
+    pub fn sheets(&mut self) -> HashMap<String, SheetParts> {
+        let styles = match self.bytes("xl/styles.xml") {
+            Ok(Some(xml)) => styles(&xml).unwrap_or_default(),
+            _ => Styles::default(),
+        };
+        let shared = self.shared_runs().unwrap_or_default();
+        let mut sheets = HashMap::new();
+        for (name, path) in self.sheets.clone() {
+            let Ok(Some(xml)) = self.bytes(&path) else {
+                continue;
+            };
+            let Ok(scan) = scan_sheet(&xml) else {
+                continue;
+            };
+            let relationships = self.relationships(&path).unwrap_or_default();
+            let mut parts = SheetParts {
+                rich_texts: scan.rich_texts,
+                ..Default::default()
+            };
+            for (cell, style) in scan.styles {
+                let format = styles.formats.get(style);
+                if let Some(code) =
+                    format.filter(|x| !x.is_empty() && !x.eq_ignore_ascii_case("general"))
+                {
+                    parts.number_formats.insert(cell, code.clone());
+                }
+                if let Some(font) = styles.fonts.get(style).filter(|x| !x.is_plain()) {
+                    parts.text_styles.insert(cell, font.clone());
+                }
+            }
+            for (cell, index) in scan.shared_strings {
+                if let Some(Some(runs)) = shared.get(index) {
+                    parts.rich_texts.insert(cell, runs.clone());
+                }
+            }
+            // links that only point at a place inside the workbook lead
+            // nowhere on a page
+            for link in scan.hyperlinks {
+                let Some(target) = link.id.and_then(|x| relationships.get(&x)) else {
+                    continue;
+                };
+                let target = match link.location {
+                    Some(location) => format!("{target}#{location}"),
+                    None => target.clone(),
+                };
+                for cell in cell_area(&link.area) {
+                    parts.hyperlinks.insert(cell, target.clone());
+                }
+            }
+            if let Some(drawing) = scan.drawing.and_then(|x| relationships.get(&x)) {
+                parts.images = self.images(drawing).unwrap_or_default();
+            }
+            sheets.insert(name, parts);
+        }
+        sheets
+    }
+
+    /// the bytes of the part at `name`, `None` when the package has no such
+    /// part
+    fn bytes(&mut self, name: &str) -> Result<Option<Vec<u8>>, Error> {
+        let mut file = match self.zip.by_name(name) {
+            Ok(file) => file,
+            Err(ZipError::FileNotFound) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        Ok(Some(bytes))
+    }
+
+    /// the targets of the relationships of part `name` by their id, as paths
+    /// inside the package
+    fn relationships(&mut self, name: &str) -> Result<HashMap<String, String>, Error> {
+        let (dir, file) = name.rsplit_once('/').unwrap_or(("", name));
+        let Some(rels) = self.bytes(&format!("{dir}/_rels/{file}.rels"))? else {
+            return Ok(HashMap::new());
+        };
+        let mut relationships = HashMap::new();
+        let mut reader = Reader::from_reader(rels.as_slice());
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"Relationship" => {
+                    let external = attribute(&e, b"TargetMode")?.as_deref() == Some("External");
+                    if let (Some(id), Some(target)) =
+                        (attribute(&e, b"Id")?, attribute(&e, b"Target")?)
+                    {
+                        let target = if external {
+                            target
+                        } else {
+                            resolve(dir, &target)
+                        };
+                        relationships.insert(id, target);
+                    }
+                }
+                Event::Eof => break,
+                _ => (),
+            }
+        }
+        Ok(relationships)
+    }
+
+    /// the runs of every shared string, `None` for the ones without runs
+    fn shared_runs(&mut self) -> Result<Vec<Option<Vec<Run>>>, Error> {
+        let Some(xml) = self.bytes("xl/sharedStrings.xml")? else {
+            return Ok(Vec::new());
+        };
+        let mut strings = Vec::new();
+        let mut reader = Reader::from_reader(xml.as_slice());
+        loop {
+            match reader.read_event()? {
+                Event::Start(e) if e.local_name().as_ref() == b"si" => {
+                    strings.push(runs(&mut reader, b"si")?);
+                }
+                Event::Empty(e) if e.local_name().as_ref() == b"si" => strings.push(None),
+                Event::Eof => break,
+                _ => (),
+            }
+        }
+        Ok(strings)
+    }
+
+    /// the pictures of drawing part `name` by the cell they are anchored to.
+    /// A cell with more than one picture keeps the first
+    fn images(&mut self, name: &str) -> Result<Images, Error> {
+        let Some(xml) = self.bytes(name)? else {
+            return Ok(Images::new());
+        };
+        let relationships = self.relationships(name)?;
+        let mut images = Images::new();
+        for (cell, target) in anchors(&xml, &relationships)? {
+            let Some(data) = self.bytes(&target)? else {
+                continue;
+            };
+            let image = Image {
+                mime: image_mime(&target).unwrap_or("image/png"),
+                data: data.into(),
+            };
+            images.insert(cell, image);
+        }
+        Ok(images)
+    }
+}
+
+/// The number format code and the font behind every cell style of
+/// `xl/styles.xml`, by the index cells refer to them with.
+#[derive(Default)]
+struct Styles {
+    formats: Vec<String>,
+    fonts: Vec<TextStyle>,
+}
+
+/// reads `xl/styles.xml`, which lists its formats and fonts before the cell
+/// styles that use them
+fn styles(xml: &[u8]) -> Result<Styles, Error> {
+    let mut codes: HashMap<u32, String> = HashMap::new();
+    let mut fonts = Vec::new();
+    let mut styles = Styles::default();
+    let (mut in_fonts, mut in_cell_xfs, mut font) = (false, false, None);
+    let mut reader = Reader::from_reader(xml);
+    loop {
+        let (e, empty) = match reader.read_event()? {
+            Event::Start(e) => (e, false),
+            Event::Empty(e) => (e, true),
+            Event::End(e) => {
+                match e.local_name().as_ref() {
+                    b"fonts" => in_fonts = false,
+                    b"cellXfs" => in_cell_xfs = false,
+                    b"font" => fonts.extend(font.take()),
+                    _ => (),
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+        match e.local_name().as_ref() {
+            b"fonts" => in_fonts = !empty,
+            b"cellXfs" => in_cell_xfs = !empty,
+            b"font" if in_fonts && empty => fonts.push(TextStyle::default()),
+            b"font" if in_fonts => font = Some(TextStyle::default()),
+            b"numFmt" => {
+                let id = attribute(&e, b"numFmtId")?.and_then(|x| x.parse().ok());
+                if let (Some(id), Some(code)) = (id, attribute(&e, b"formatCode")?) {
+                    codes.insert(id, code);
+                }
+            }
+            b"xf" if in_cell_xfs => {
+                let id: u32 = attribute(&e, b"numFmtId")?
+                    .and_then(|x| x.parse().ok())
+                    .unwrap_or_default();
+                let code = codes
+                    .get(&id)
+                    .cloned()
+                    .or_else(|| builtin_format(id).map(String::from))
+                    .unwrap_or_default();
+                styles.formats.push(code);
+                let font = attribute(&e, b"fontId")?
+                    .and_then(|x| x.parse::<usize>().ok())
+                    .and_then(|x| fonts.get(x))
+                    .cloned()
+                    .unwrap_or_default();
+                styles.fonts.push(font);
+            }
+            _ => {
+                if let Some(font) = &mut font {
+                    font.apply(&e)?;
+                }
+            }
+        }
+    }
+    Ok(styles)
+}
+
+/// A `<hyperlink>` of a sheet, its target is in the sheet relationships.
+struct HyperlinkRef {
+    area: String,
+    id: Option<String>,
+    location: Option<String>,
+}
+
+/// What one pass over a sheet part finds.
+#[derive(Default)]
+struct SheetScan {
+    styles: CellStyles,
+    /// inline rich texts
+    rich_texts: RichTexts,
+    /// the shared string index of every cell holding one
+    shared_strings: HashMap<(u32, u32), usize>,
+    hyperlinks: Vec<HyperlinkRef>,
+    /// the relationship id of the drawing of the sheet, if it has one
+    drawing: Option<String>,
+}
+
+fn scan_sheet(xml: &[u8]) -> Result<SheetScan, Error> {
+    let mut scan = SheetScan::default();
+    let mut position = CellPosition::default();
+    let (mut cell, mut shared_string) = ((0, 0), false);
+    let mut reader = Reader::from_reader(xml);
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"row" => {
+                position.row(&e)?;
+            }
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"c" => {
+                cell = position.cell(&e)?;
+                shared_string = attribute(&e, b"t")?.as_deref() == Some("s");
+                if let Some(style) = attribute(&e, b"s")?.and_then(|x| x.parse().ok()) {
+                    scan.styles.insert(cell, style);
+                }
+            }
+            Event::Start(e) if e.local_name().as_ref() == b"v" && shared_string => {
+                if let Ok(index) = reader.read_text(e.name())?.trim().parse() {
+                    scan.shared_strings.insert(cell, index);
+                }
+            }
+            Event::Start(e) if e.local_name().as_ref() == b"is" => {
+                if let Some(runs) = runs(&mut reader, b"is")? {
+                    scan.rich_texts.insert(cell, runs);
+                }
+            }
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"hyperlink" => {
+                if let Some(area) = attribute(&e, b"ref")? {
+                    scan.hyperlinks.push(HyperlinkRef {
+                        area,
+                        id: attribute(&e, b"id")?,
+                        location: attribute(&e, b"location")?,
+                    });
+                }
+            }
+            Event::Start(e) | Event::Empty(e) if e.local_name().as_ref() == b"drawing" => {
+                scan.drawing = attribute(&e, b"id")?;
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+    Ok(scan)
+}
+
+/// the picture part of every cell with a picture in a drawing part, by
+/// (row, column). Pictures that float free of the cells are left out
+fn anchors(
+    xml: &[u8],
+    relationships: &HashMap<String, String>,
+) -> Result<HashMap<(u32, u32), String>, Error> {
+    let mut anchors = HashMap::new();
+    let (mut in_from, mut row, mut col, mut picture) = (false, None, None, None);
+    let mut reader = Reader::from_reader(xml);
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                b"twoCellAnchor" | b"oneCellAnchor" => (row, col, picture) = (None, None, None),
+                b"from" => in_from = true,
+                b"row" if in_from => row = reader.read_text(e.name())?.trim().parse().ok(),
+                b"col" if in_from => col = reader.read_text(e.name())?.trim().parse().ok(),
+                b"blip" => picture = attribute(&e, b"embed")?,
+                _ => (),
+            },
+            Event::End(e) => match e.local_name().as_ref() {
+                b"from" => in_from = false,
+                b"twoCellAnchor" | b"oneCellAnchor" => {
+                    let target = picture.take().and_then(|x| relationships.get(&x));
+                    if let (Some(row), Some(col), Some(target)) = (row, col, target) {
+                        anchors.entry((row, col)).or_insert_with(|| target.clone());
+                    }
+                }
+                _ => (),
+            },
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+    Ok(anchors)
+}
+
+/// the runs of the `<si>` or `<is>` the reader just entered, up to its end
+/// tag `end`. `None` when none of the runs has its own font, the text is
+/// plain then
+fn runs(reader: &mut Reader<&[u8]>, end: &[u8]) -> Result<Option<Vec<Run>>, Error> {
+    let mut runs = Vec::new();
+    let mut run: Option<Run> = None;
+    let mut in_properties = false;
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => match e.local_name().as_ref() {
+                b"r" => {
+                    run = Some(Run {
+                        text: String::new(),
+                        style: None,
+                    })
+                }
+                b"rPr" => {
+                    in_properties = true;
+                    if let Some(run) = &mut run {
+                        run.style = Some(TextStyle::default());
+                    }
+                }
+                b"t" => {
+                    let text = reader.read_text(e.name())?;
+                    let text = unescape(&text).map_err(quick_xml::Error::from)?;
+                    if let Some(run) = &mut run {
+                        run.text.push_str(&text);
+                    }
+                }
+                // phonetic guides are not part of the text
+                b"rPh" => {
+                    reader.read_to_end(e.name())?;
+                }
+                _ if in_properties => {
+                    if let Some(style) = run.as_mut().and_then(|x| x.style.as_mut()) {
+                        style.apply(&e)?;
+                    }
+                }
+                _ => (),
+            },
+            Event::Empty(e) if in_properties => {
+                if let Some(style) = run.as_mut().and_then(|x| x.style.as_mut()) {
+                    style.apply(&e)?;
+                }
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"rPr" => in_properties = false,
+                b"r" => runs.extend(run.take()),
+                name if name == end => break,
+                _ => (),
+            },
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+    let styled = runs.iter().any(|x| x.style.is_some());
+    Ok(styled.then_some(runs))
+}
+
+/// the css color of a `<color>`, only colors given as rgb or as one of the
+/// first sixteen indexes are known, theme colors are not
+fn color(e: &BytesStart) -> Result<Option<String>, Error> {
+    if let Some(rgb) = attribute(e, b"rgb")? {
+        // ARGB, the alpha is left out
+        let valid = rgb.len() >= 6 && rgb.chars().all(|x| x.is_ascii_hexdigit());
+        return Ok(valid.then(|| format!("#{}", &rgb[rgb.len() - 6..])));
+    }
+    const INDEXED: [&str; 16] = [
+        "#000000", "#FFFFFF", "#FF0000", "#00FF00", "#0000FF", "#FFFF00", "#FF00FF", "#00FFFF",
+        "#000000", "#FFFFFF", "#FF0000", "#00FF00", "#0000FF", "#FFFF00", "#FF00FF", "#00FFFF",
+    ];
+    let index = attribute(e, b"indexed")?.and_then(|x| x.parse::<usize>().ok());
+    Ok(index.and_then(|x| INDEXED.get(x)).map(|x| x.to_string()))
+}
+
+/// the media type browsers know a picture by, from its extension, `None`
+/// when the extension is not a picture one
+pub fn image_mime(path: &str) -> Option<&'static str> {
+    let extension = path.rsplit_once('.').map(|x| x.1.to_ascii_lowercase());
+    Some(match extension.as_deref()? {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "bmp" => "image/bmp",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "tif" | "tiff" => "image/tiff",
+        "emf" => "image/emf",
+        "wmf" => "image/wmf",
+        _ => return None,
+    })
+}
+
+/// Where the reader is in a sheet, cells and rows may leave out their `r`
+/// and then just follow the previous one.
+#[derive(Default)]
+struct CellPosition {
+    row: u32,
+    next_column: u32,
+    seen_row: bool,
+}
+
+impl CellPosition {
+    fn row(&mut self, e: &BytesStart) -> Result<(), Error> {
+        self.row = match attribute(e, b"r")?.and_then(|x| x.parse::<u32>().ok()) {
+            Some(number) => number.saturating_sub(1),
+            None if self.seen_row => self.row + 1,
+            None => 0,
+        };
+        self.seen_row = true;
+        self.next_column = 0;
+        Ok(())
+    }
+
+    fn cell(&mut self, e: &BytesStart) -> Result<(u32, u32), Error> {
+        let cell = attribute(e, b"r")?
+            .and_then(|x| cell_position(&x))
+            .unwrap_or((self.row, self.next_column));
+        self.next_column = cell.1 + 1;
+        Ok(cell)
+    }
+}
+
+/// `B3` as (2, 1)
+fn cell_position(reference: &str) -> Option<(u32, u32)> {
+    let split = reference.find(|x: char| x.is_ascii_digit())?;
+    let (letters, digits) = reference.split_at(split);
+    let row = digits.parse::<u32>().ok()?.checked_sub(1)?;
+    Some((row, column_index(letters)? as u32))
+}
+
+/// every cell of `A2:B3`, a single reference like `A2` is just that cell
+fn cell_area(reference: &str) -> Vec<(u32, u32)> {
+    let (start, end) = reference.split_once(':').unwrap_or((reference, reference));
+    let (Some(start), Some(end)) = (cell_position(start), cell_position(end)) else {
+        return Vec::new();
+    };
+    (start.0..=end.0)
+        .flat_map(|row| (start.1..=end.1).map(move |col| (row, col)))
+        .collect()
+}
+
+fn attribute(e: &BytesStart, name: &[u8]) -> Result<Option<String>, Error> {
+    for attribute in e.attributes() {
+        let attribute = attribute.map_err(quick_xml::Error::from)?;
+        if attribute.key.local_name().as_ref() == name {
+            return Ok(Some(
+                attribute
+                    .decode_and_unescape_value(e.decoder())?
+                    .into_owned(),
+            ));
+        }
+    }
+    Ok(None)
+}
+
+/// a relationship target as a path inside the package, relative targets
+/// start from the folder of the part they belong to
+fn resolve(dir: &str, target: &str) -> String {
+    let mut parts: Vec<&str> = match target.strip_prefix('/') {
+        Some(_) => Vec::new(),
+        None => dir.split('/').filter(|x| !x.is_empty()).collect(),
+    };
+    for part in target.split('/') {
+        match part {
+            "" | "." => (),
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// the number formats every workbook has without listing them
+fn builtin_format(id: u32) -> Option<&'static str> {
+    let code = match id {
+        1 => "0",
+        2 => "0.00",
+        3 => "#,##0",
+        4 => "#,##0.00",
+        9 => "0%",
+        10 => "0.00%",
+        11 => "0.00E+00",
+        37 => "#,##0 ;(#,##0)",
+        38 => "#,##0 ;[Red](#,##0)",
+        39 => "#,##0.00;(#,##0.00)",
+        40 => "#,##0.00;[Red](#,##0.00)",
+        48 => "##0.0E+0",
+        _ => return None,
+    };
+    Some(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn targets_resolve_inside_the_package() {
+        assert_eq!(
+            resolve("xl", "worksheets/sheet1.xml"),
+            "xl/worksheets/sheet1.xml"
+        );
+        assert_eq!(
+            resolve("xl/drawings", "../media/image1.png"),
+            "xl/media/image1.png"
+        );
+        assert_eq!(
+            resolve("xl", "/xl/worksheets/sheet2.xml"),
+            "xl/worksheets/sheet2.xml"
+        );
+        assert_eq!(cell_position("AB12"), Some((11, 27)));
+        assert_eq!(cell_position("A0"), None);
+        assert_eq!(cell_area("B2:C3"), [(1, 1), (1, 2), (2, 1), (2, 2)]);
+        assert_eq!(cell_area("A1"), [(0, 0)]);
+    }
+
+    #[test]
+    fn a_sheet_is_read_in_one_pass() {
+        let xml = br#"<styleSheet><numFmts><numFmt numFmtId="164" formatCode="0.0%"/></numFmts><fonts><font/><font><i/></font></fonts><cellXfs><xf numFmtId="0" fontId="0"/><xf numFmtId="164" fontId="1"/><xf numFmtId="3"/></cellXfs><dxfs><dxf><font><b/></font></dxf></dxfs></styleSheet>"#;
+        let styles = styles(xml).unwrap();
+        assert_eq!(styles.formats, ["", "0.0%", "#,##0"]);
+        assert!(styles.fonts[1].italic && !styles.fonts[1].bold);
+        assert_eq!(styles.fonts.len(), 3);
+
+        let xml = br#"<worksheet><sheetData><row r="2"><c r="A2" s="1"><v>1</v></c><c t="s"><v>4</v></c></row></sheetData><hyperlinks><hyperlink ref="A2" r:id="rId1"/></hyperlinks><drawing r:id="rId2"/></worksheet>"#;
+        let scan = scan_sheet(xml).unwrap();
+        assert_eq!(scan.styles, HashMap::from([((1, 0), 1)]));
+        assert_eq!(scan.shared_strings, HashMap::from([((1, 1), 4)]));
+        assert_eq!(scan.hyperlinks[0].area, "A2");
+        assert_eq!(scan.hyperlinks[0].id.as_deref(), Some("rId1"));
+        assert_eq!(scan.drawing.as_deref(), Some("rId2"));
+    }
+
+    #[test]
+    fn pictures_belong_to_the_cell_of_their_corner() {
+        let xml = br#"<xdr:wsDr xmlns:xdr="x" xmlns:a="a" xmlns:r="r">
+            <xdr:oneCellAnchor><xdr:from><xdr:col>1</xdr:col><xdr:colOff>0</xdr:colOff><xdr:row>2</xdr:row><xdr:rowOff>0</xdr:rowOff></xdr:from>
+                <xdr:pic><xdr:blipFill><a:blip r:embed="rId1"/></xdr:blipFill></xdr:pic></xdr:oneCellAnchor>
+            <xdr:twoCellAnchor><xdr:from><xdr:col>3</xdr:col><xdr:row>0</xdr:row></xdr:from><xdr:to><xdr:col>5</xdr:col><xdr:row>4</xdr:row></xdr:to>
+                <xdr:pic><xdr:blipFill><a:blip r:embed="rId2"/></xdr:blipFill></xdr:pic></xdr:twoCellAnchor>
+            <xdr:twoCellAnchor><xdr:from><xdr:col>0</xdr:col><xdr:row>0</xdr:row></xdr:from><xdr:to><xdr:col>1</xdr:col><xdr:row>1</xdr:row></xdr:to>
+                <xdr:pic><xdr:blipFill><a:blip r:embed="rId9"/></xdr:blipFill></xdr:pic></xdr:twoCellAnchor>
+            <xdr:absoluteAnchor><xdr:pic><xdr:blipFill><a:blip r:embed="rId1"/></xdr:blipFill></xdr:pic></xdr:absoluteAnchor>
+        </xdr:wsDr>"#;
+        let relationships = HashMap::from([
+            ("rId1".to_string(), "xl/media/image1.png".to_string()),
+            ("rId2".to_string(), "xl/media/image2.jpeg".to_string()),
+        ]);
+        assert_eq!(
+            anchors(xml, &relationships).unwrap(),
+            HashMap::from([
+                ((2, 1), "xl/media/image1.png".to_string()),
+                ((0, 3), "xl/media/image2.jpeg".to_string()),
+            ])
+        );
+        assert_eq!(image_mime("xl/media/image2.jpeg"), Some("image/jpeg"));
+        assert_eq!(image_mime("xl/media/image3.PNG"), Some("image/png"));
+        assert_eq!(image_mime("xl/media/notes.txt"), None);
+    }
+
+    #[test]
+    fn rich_text_keeps_its_runs() {
+        let xml = r#"<si><r><t>plain </t></r><r><rPr><b/><color rgb="FFFF0000"/></rPr><t>red &amp; bold</t></r><rPh><t>x</t></rPh></si>"#;
+        let mut reader = Reader::from_str(xml);
+        reader.read_event().unwrap();
+        let styled = runs(&mut reader, b"si").unwrap().unwrap();
+        assert_eq!(
+            styled,
+            [
+                Run {
+                    text: "plain ".into(),
+                    style: None,
+                },
+                Run {
+                    text: "red & bold".into(),
+                    style: Some(TextStyle {
+                        bold: true,
+                        color: Some("#FF0000".into()),
+                        ..Default::default()
+                    }),
+                },
+            ]
+        );
+        let mut reader = Reader::from_str("<si><t>all plain</t></si>");
+        reader.read_event().unwrap();
+        assert_eq!(runs(&mut reader, b"si").unwrap(), None);
+    }
+}